@@ -0,0 +1,500 @@
+//! The i3switch command, executing the parsed command line with the chosen backend.
+
+use crate::backend::*;
+use crate::cli;
+use crate::config;
+#[cfg(feature = "i3")]
+use crate::daemon;
+use crate::error;
+use crate::history;
+use crate::logging;
+use crate::navigation;
+use crate::paths;
+use crate::query;
+use crate::urgency;
+
+use std::path::{Path, PathBuf};
+
+/// Runs the command given by the arguments, the first one being the program name, and returns
+/// the process exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    // Help is given even when the configuration is broken
+    cli::Cli::print_info(&args);
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => return fail(e),
+    };
+    if let Some(level) = config.log_level {
+        logging::set_level(level);
+    }
+    let cli = cli::Cli::parse(args.clone(), &config);
+    if let Some(level) = cli.log_level {
+        logging::set_level(level);
+    }
+
+    #[cfg(feature = "i3")]
+    if cli.command == "daemon" && [cli::UseBackend::I3, cli::UseBackend::Auto].contains(&cli.backend) {
+        logging::info!("Starting daemon.");
+        return daemon::serve(config, cli.history_depth).map_or_else(fail, |()| error::EXIT_SUCCESS);
+    }
+
+    let code = switch(&cli, &args[1..]);
+    // The command is run by the process that was asked for it, in the environment and directory
+    // it was given in, even when the daemon found nothing to raise
+    if cli.command == "raise-or-run" && !cli.dry_run && code == error::EXIT_NOTHING {
+        return spawn(&cli.run).map_or_else(fail, |()| error::EXIT_SUCCESS);
+    }
+    code
+}
+
+/// Executes the command, by the daemon when there is one, and returns the process exit code.
+fn switch(cli: &cli::Cli, args: &[String]) -> i32 {
+    // Only the i3 backend has a daemon, it is found as well when the backend is detected
+    #[cfg(feature = "i3")]
+    if [cli::UseBackend::I3, cli::UseBackend::Auto].contains(&cli.backend) {
+        // Reports are printed by the process that was asked for them
+        if cli.command != "query" && !cli.dry_run {
+            if let Some(code) = daemon::forward(args) {
+                return code;
+            }
+        }
+    }
+    #[cfg(not(feature = "i3"))]
+    let _ = args;
+
+    match connect(cli.backend) {
+        Ok(mut backend) => execute(cli, &mut backend),
+        Err(e) => fail(e),
+    }
+}
+
+/// Connects to the backend chosen on the command line.
+fn connect(backend: cli::UseBackend) -> error::Result<Backend> {
+    match backend {
+        cli::UseBackend::Auto => detect(),
+        #[cfg(feature = "i3")]
+        cli::UseBackend::I3 => {
+            logging::info!("Connecting to I3 backend.");
+            Ok(Backend::new(UsedBackend::I3(I3Backend::new()?)))
+        }
+        #[cfg(feature = "wmctl")]
+        cli::UseBackend::WmCtl => {
+            logging::info!("Connecting to WmCtl backend.");
+            Ok(Backend::new(UsedBackend::WmCtl(WmctlBackend::new()?)))
+        }
+        #[cfg(feature = "xcb")]
+        cli::UseBackend::Xcb => {
+            logging::info!("Connecting to XCB backend.");
+            Ok(Backend::new(UsedBackend::Xcb(Box::new(XcbBackend::new()?))))
+        }
+    }
+}
+
+/// Connects to the first backend that works with the running window manager, logging why the
+/// others don't. The i3 backend finds the IPC socket of i3 or sway, the xcb backend requires the
+/// EWMH atoms it relies on to be supported.
+fn detect() -> error::Result<Backend> {
+    probe(cli::UseBackend::iter().as_slice(), connect)
+}
+
+/// Connects to the first of the backends that works, in their order. When none does, the error
+/// tells why for each of them.
+fn probe<T, F>(backends: &[cli::UseBackend], connect: F) -> error::Result<T>
+where
+    F: Fn(cli::UseBackend) -> error::Result<T>,
+{
+    if backends.is_empty() {
+        return Err(error::Error::Connection("No backend is built in".to_string()));
+    }
+    let mut reasons = Vec::new();
+    for backend in backends {
+        match connect(*backend) {
+            Ok(connected) => {
+                logging::info!("Detected {:?} backend.", backend);
+                return Ok(connected);
+            }
+            Err(e) => {
+                logging::info!("Not using {:?} backend: {}", backend, e);
+                reasons.push(format!("{:?}: {}", backend, e));
+            }
+        }
+    }
+    Err(error::Error::Connection(format!("No backend works with the window manager, {}", reasons.join(", "))))
+}
+
+/// Logs the error and returns the exit code documented for it.
+fn fail(error: error::Error) -> i32 {
+    logging::error!("{}", error);
+    error.exit_code()
+}
+
+/// Executes the parsed command with the given backend and returns the process exit code.
+/// Shared between regular invocations and the daemon, which keeps its backend between commands.
+pub(crate) fn execute<B>(cli: &cli::Cli, backend: &mut B) -> i32
+where
+    B: GetVisible + GetTabs + GetGroups + GetOutputs + GetAll + SetFocus + MoveWindow
+        + LeaveFullscreen + GetWorkspaces + FocusWorkspace + GetScratchpad + ShowScratchpad
+        + GetContainers,
+{
+    let result = if cli.command == "query" {
+        query::execute(cli, backend, runtime_dir().as_deref())
+    } else if cli.scope == "workspace" {
+        execute_workspace(cli, backend)
+    } else if cli.scope == "scratchpad" {
+        execute_scratchpad(cli, backend)
+    } else {
+        execute_window(cli, backend, runtime_dir().as_deref())
+    };
+    result.unwrap_or_else(fail)
+}
+
+/// Returns the directory the history and the order of urgent windows are kept in, or `None` when
+/// there is no private one, commands then work without them.
+fn runtime_dir() -> Option<PathBuf> {
+    match paths::runtime_dir() {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            logging::warning!("No runtime directory to keep history in: {}", e);
+            None
+        }
+    }
+}
+
+/// Executes the command on windows and returns the process exit code. The history and the order
+/// of urgent windows are kept in the runtime directory.
+fn execute_window<B>(cli: &cli::Cli, backend: &mut B, runtime: Option<&Path>) -> error::Result<i32>
+where
+    B: GetVisible + GetTabs + GetGroups + GetOutputs + GetAll + SetFocus + MoveWindow
+        + LeaveFullscreen + GetContainers,
+{
+    let (mut history, focused) = load_history(cli, backend, runtime)?;
+    let mut urgency = runtime.map(urgency::Urgency::load).unwrap_or_default();
+    leave_fullscreen(cli, backend)?;
+
+    let window_id = find_window(cli, backend, &mut history, &mut urgency)?;
+    if cli.dry_run {
+        // Nothing is saved before, so that the command does for real what it told
+        return Ok(query::print_dry_run(cli, window_id));
+    }
+
+    // Only focusing changes the history, stepping through it included, it is saved no other time
+    let Some(window_id) = window_id else {
+        return Ok(error::EXIT_NOTHING);
+    };
+
+    if cli.action == "focus" {
+        backend.set_focus(&window_id)?;
+        history.focus(window_id, cli.history_depth);
+        if let Some(runtime) = runtime {
+            history.save(runtime);
+            // The order of urgent windows is caught up only when one of them is attended
+            if cli.command == "urgent" {
+                urgency.save(runtime);
+            }
+        }
+        return Ok(error::EXIT_SUCCESS);
+    }
+
+    // Moving windows keeps the focus where it was, so the history stays the same
+    let focused = focused.ok_or(error::Error::Missing("No focused window to move".to_string()))?;
+    if cli.action == "swap" {
+        backend.swap_windows(&focused, &window_id)?;
+    } else {
+        backend.move_window(&focused, &window_id)?;
+    }
+    Ok(error::EXIT_SUCCESS)
+}
+
+/// Leaves fullscreen of the focused window before a direction, when the policy says so, so that
+/// the windows behind it can be switched to. A dry run only tells it would.
+fn leave_fullscreen<B: GetVisible + LeaveFullscreen>(cli: &cli::Cli, backend: &mut B) -> error::Result<()> {
+    if cli.planar_direction().is_none() || cli.navigation.fullscreen != navigation::Fullscreen::Leave {
+        return Ok(());
+    }
+    let Some(window_id) = navigation::get_fullscreen_window(backend)? else {
+        return Ok(());
+    };
+    if cli.dry_run {
+        logging::warning!("Dry run doesn't leave fullscreen, windows hidden behind it may be missing.");
+        return Ok(());
+    }
+    backend.leave_fullscreen(&window_id)
+}
+
+/// Loads the focus history from the runtime directory and returns it with the focused window.
+/// Focus could have changed without us, history has to catch up before it is used.
+pub(crate) fn load_history<B: GetVisible>(cli: &cli::Cli, backend: &B, runtime: Option<&Path>) -> error::Result<(history::History, Option<u64>)> {
+    let mut history = runtime.map(history::History::load).unwrap_or_default();
+    let focused = navigation::get_focused_window(backend)?;
+    if let Some(focused) = focused {
+        history.focus(focused, cli.history_depth);
+    }
+    Ok((history, focused))
+}
+
+/// Determines the window the command leads to, without focusing it. The history and the order of
+/// urgent windows are updated as the command goes through them, the caller saves them.
+pub(crate) fn find_window<B>(cli: &cli::Cli, backend: &B, history: &mut history::History, urgency: &mut urgency::Urgency) -> error::Result<Option<u64>>
+where
+    B: GetVisible + GetTabs + GetGroups + GetOutputs + GetAll + GetContainers,
+{
+    let wrap = cli.wrap.is_some();
+
+    if let Some(direction) = cli.linear_direction() {
+        logging::info!("Switching focus in linear direction: {:?}", direction);
+        navigation::get_window_to_switch_to(backend, &cli.navigation, direction, wrap)
+    } else if let Some(direction) = cli.planar_direction() {
+        logging::info!("Switching focus in planar direction: {:?}", direction);
+        navigation::get_window_in_direction(backend, &cli.navigation, direction, cli.wrap, cli.group)
+    } else if let Some(direction) = cli.history_direction() {
+        logging::info!("Switching focus in history direction: {:?}", direction);
+        navigation::get_window_in_history(backend, &cli.navigation, history, direction, wrap)
+    } else if cli.command == "back" {
+        logging::info!("Switching focus to previous window");
+        navigation::get_previous_window(backend, &cli.navigation, history)
+    } else if cli.command == "parent" {
+        logging::info!("Switching focus to parent container");
+        navigation::get_parent_container(backend)
+    } else if cli.command == "child" {
+        logging::info!("Switching focus to child container");
+        navigation::get_child_container(backend)
+    } else if let Some(query) = &cli.query {
+        logging::info!("Switching focus to window matching: {}", query);
+        navigation::get_window_matching(backend, &cli.navigation, query)
+    } else if cli.command == "raise-or-run" {
+        logging::info!("Switching focus to window meeting: {:?}", cli.criteria);
+        navigation::get_window_to_raise(backend, &cli.navigation, &cli.criteria)
+    } else if cli.command == "urgent" {
+        logging::info!("Switching focus to urgent window");
+        navigation::get_urgent_window(backend, &cli.navigation, urgency)
+    } else if let Some(number) = cli.number {
+        logging::info!("Switching focus to window number: {}", number);
+        if wrap {
+            logging::warning!("Wrap option is ignored for number switching.");
+        }
+        navigation::get_window_of_number(backend, &cli.navigation, number)
+    } else {
+        unreachable!("No valid command provided. This should not happen.");
+    }
+}
+
+/// Runs the command in the background, only whether it could be started is known.
+fn spawn(command: &[String]) -> error::Result<()> {
+    logging::info!("Running command: {:?}", command);
+    std::process::Command::new(&command[0]).args(&command[1..]).spawn()
+        .map_err(|e| error::Error::Run(format!("Failed to run '{}': {}", command[0], e)))?;
+    Ok(())
+}
+
+/// Executes the command on workspaces instead of windows and returns the process exit code.
+fn execute_workspace<B: GetWorkspaces + FocusWorkspace>(cli: &cli::Cli, backend: &mut B) -> error::Result<i32> {
+    let workspace_id = find_workspace(cli, backend)?;
+    if cli.dry_run {
+        return Ok(query::print_dry_run(cli, workspace_id));
+    }
+
+    let Some(workspace_id) = workspace_id else {
+        return Ok(error::EXIT_NOTHING);
+    };
+    backend.focus_workspace(&workspace_id)?;
+    Ok(error::EXIT_SUCCESS)
+}
+
+/// Determines the workspace the command leads to, without focusing it.
+pub(crate) fn find_workspace<B: GetWorkspaces>(cli: &cli::Cli, backend: &B) -> error::Result<Option<u64>> {
+    let wrap = cli.wrap.is_some();
+
+    if let Some(direction) = cli.linear_direction() {
+        logging::info!("Switching workspace in linear direction: {:?}", direction);
+        navigation::get_workspace_to_switch_to(backend, direction, wrap)
+    } else if let Some(direction) = cli.planar_direction() {
+        logging::info!("Switching workspace in planar direction: {:?}", direction);
+        navigation::get_workspace_in_direction(backend, direction, wrap)
+    } else if let Some(number) = cli.number {
+        logging::info!("Switching to workspace number: {}", number);
+        navigation::get_workspace_of_number(backend, number)
+    } else {
+        unreachable!("No valid workspace command provided. This should not happen.");
+    }
+}
+
+/// Executes the command on scratchpad windows and returns the process exit code.
+/// The focused window shown from the scratchpad is hidden first, so that cycling through the
+/// scratchpad shows one window at a time, like switching tabs.
+fn execute_scratchpad<B: GetScratchpad + ShowScratchpad>(cli: &cli::Cli, backend: &mut B) -> error::Result<i32> {
+    let window_id = find_scratchpad_window(cli, backend)?;
+    if cli.dry_run {
+        return Ok(query::print_dry_run(cli, window_id));
+    }
+
+    let Some(window_id) = window_id else {
+        return Ok(error::EXIT_NOTHING);
+    };
+    match navigation::get_focused_scratchpad_window(backend)? {
+        Some(shown) if shown == window_id => return Ok(error::EXIT_SUCCESS),
+        Some(shown) => backend.hide_scratchpad(&shown)?,
+        None => {}
+    }
+    backend.show_scratchpad(&window_id)?;
+    Ok(error::EXIT_SUCCESS)
+}
+
+/// Determines the scratchpad window the command leads to, without showing it.
+pub(crate) fn find_scratchpad_window<B: GetScratchpad>(cli: &cli::Cli, backend: &B) -> error::Result<Option<u64>> {
+    let wrap = cli.wrap.is_some();
+
+    if let Some(direction) = cli.linear_direction() {
+        logging::info!("Showing scratchpad window in linear direction: {:?}", direction);
+        navigation::get_scratchpad_window_to_switch_to(backend, &cli.navigation, direction, wrap)
+    } else if let Some(number) = cli.number {
+        logging::info!("Showing scratchpad window number: {}", number);
+        navigation::get_scratchpad_window_of_number(backend, &cli.navigation, number)
+    } else {
+        unreachable!("No valid scratchpad command provided. This should not happen.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Error, Result};
+    use crate::types::{Outputs, Rect, Window, Windows};
+
+    struct Screen(Windows);
+
+    impl GetVisible for Screen {
+        fn get_visible(&self) -> Result<Windows> {
+            Ok(self.0.clone())
+        }
+    }
+
+    impl GetTabs for Screen {
+        fn get_tabs(&self) -> Result<Windows> {
+            Ok(vec![])
+        }
+    }
+
+    impl GetGroups for Screen {
+        fn get_groups(&self) -> Result<Windows> {
+            self.get_visible()
+        }
+    }
+
+    impl GetOutputs for Screen {
+        fn get_outputs(&self) -> Result<Outputs> {
+            Err(Error::Unsupported("Outputs not supported in this backend".to_string()))
+        }
+    }
+
+    impl GetAll for Screen {
+        fn get_all(&self) -> Result<Windows> {
+            Ok(self.0.clone())
+        }
+    }
+
+    impl GetContainers for Screen {
+        fn get_parent(&self) -> Result<Option<u64>> {
+            Ok(None)
+        }
+
+        fn get_child(&self) -> Result<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    impl SetFocus for Screen {
+        fn set_focus(&mut self, window_id: &u64) -> Result<()> {
+            self.0.iter_mut().for_each(|w| w.focused = w.id == *window_id);
+            Ok(())
+        }
+    }
+
+    impl LeaveFullscreen for Screen {
+        fn leave_fullscreen(&mut self, _window_id: &u64) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl MoveWindow for Screen {
+        fn swap_windows(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+            Ok(())
+        }
+
+        fn move_window(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests backend detection.
+    /// We expect the backends to be tried in order up to the first that works, and the reason of
+    /// each one to be told when none does.
+    #[cfg(all(feature = "i3", feature = "xcb"))]
+    #[test]
+    fn test_probe() {
+        use cli::UseBackend;
+        let backends = [UseBackend::I3, UseBackend::Xcb];
+        let tried = std::cell::RefCell::new(Vec::new());
+        let connect = |works: Option<UseBackend>| {
+            let tried = &tried;
+            move |backend: UseBackend| {
+                tried.borrow_mut().push(backend);
+                if Some(backend) == works {
+                    Ok(backend)
+                } else {
+                    Err(Error::Connection(format!("{:?} is not running", backend)))
+                }
+            }
+        };
+
+        assert_eq!(probe(&backends, connect(Some(UseBackend::I3))), Ok(UseBackend::I3));
+        assert_eq!(tried.take(), vec![UseBackend::I3]);
+        assert_eq!(probe(&backends, connect(Some(UseBackend::Xcb))), Ok(UseBackend::Xcb));
+        assert_eq!(tried.take(), vec![UseBackend::I3, UseBackend::Xcb]);
+        assert_eq!(probe(&backends, connect(None)), Err(Error::Connection(
+            "No backend works with the window manager, I3: I3 is not running, Xcb: Xcb is not running".to_string())));
+        assert_eq!(probe(&[], connect(None)), Err(Error::Connection("No backend is built in".to_string())));
+    }
+
+    /// Tests that dry runs save nothing.
+    /// We expect repeated dry runs of urgent to pick the same window, the one a real run focuses,
+    /// and the saved state to be left as it was.
+    #[test]
+    fn test_urgent_dry_run_twice() {
+        let dir = std::env::temp_dir().join(format!("i3switch-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let runtime = Some(dir.as_path());
+
+        let window = |id: u64, x: i32, focused: bool, urgent: bool| Window {
+            id, rect: Rect { x, y: 0, w: 100, h: 100 }, focused, urgent, ..Default::default()
+        };
+        let mut backend = Screen(vec![
+            window(1, 0, true, false),
+            window(3, 100, false, true),
+            window(2, 200, false, true),
+        ]);
+        let parse = |args: &str| {
+            cli::Cli::parse(args.split_whitespace().map(String::from).collect(), &config::Config::default())
+        };
+        let target = |cli: &cli::Cli, backend: &Screen| {
+            let (mut history, _) = load_history(cli, backend, runtime).unwrap();
+            find_window(cli, backend, &mut history, &mut urgency::Urgency::load(&dir)).unwrap()
+        };
+
+        let cli = parse("i3switch --dry-run urgent");
+        let mut targets = Vec::new();
+        for _ in 0..2 {
+            targets.push(target(&cli, &backend));
+            assert_eq!(execute_window(&cli, &mut backend, runtime), Ok(error::EXIT_SUCCESS));
+        }
+        assert_eq!(targets, vec![Some(3), Some(3)]);
+        assert!(!dir.join("i3switch.urgent").exists());
+        assert!(!dir.join("i3switch.history").exists());
+
+        let cli = parse("i3switch urgent");
+        assert_eq!(execute_window(&cli, &mut backend, runtime), Ok(error::EXIT_SUCCESS));
+        assert_eq!(navigation::get_focused_window(&backend), Ok(Some(3)));
+        assert_eq!(urgency::Urgency::load(&dir).windows(), &[3, 2]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,7 +6,8 @@ use crate::backend::wmctl;
 use crate::backend::xcb;
 
 use crate::backend::traits::*;
-use crate::types::Windows;
+use crate::error::Result;
+use crate::types::{Outputs, TabLevel, Windows, Workspaces};
 
 pub enum UsedBackend {
     #[cfg(feature = "i3")]
@@ -14,7 +15,7 @@ pub enum UsedBackend {
     #[cfg(feature = "wmctl")]
     WmCtl(wmctl::Backend),
     #[cfg(feature = "xcb")]
-    Xcb(xcb::Backend),
+    Xcb(Box<xcb::Backend>),
 }
 
 pub struct Backend {
@@ -30,7 +31,7 @@ impl Backend {
 }
 
 impl GetTabs for Backend {
-    fn get_tabs(&self) -> Result<Windows, String> {
+    fn get_tabs(&self) -> Result<Windows> {
         match self.used_backend {
             #[cfg(feature = "i3")]
             UsedBackend::I3(ref i3) => i3.get_tabs(),
@@ -40,10 +41,21 @@ impl GetTabs for Backend {
             UsedBackend::Xcb(ref xcb) => xcb.get_tabs(),
         }
     }
+
+    fn get_tabs_at(&self, level: TabLevel) -> Result<Windows> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_tabs_at(level),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_tabs_at(level),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_tabs_at(level),
+        }
+    }
 }
 
 impl GetVisible for Backend {
-    fn get_visible(&self) -> Result<Windows, String> {
+    fn get_visible(&self) -> Result<Windows> {
         match self.used_backend {
             #[cfg(feature = "i3")]
             UsedBackend::I3(ref i3) => i3.get_visible(),
@@ -55,8 +67,97 @@ impl GetVisible for Backend {
     }
 }
 
+impl GetGroups for Backend {
+    fn get_groups(&self) -> Result<Windows> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_groups(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_groups(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_groups(),
+        }
+    }
+}
+
+impl GetAll for Backend {
+    fn get_all(&self) -> Result<Windows> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_all(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_all(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_all(),
+        }
+    }
+}
+
+impl GetScratchpad for Backend {
+    fn get_scratchpad(&self) -> Result<Windows> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_scratchpad(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_scratchpad(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_scratchpad(),
+        }
+    }
+}
+
+impl GetContainers for Backend {
+    fn get_parent(&self) -> Result<Option<u64>> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_parent(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_parent(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_parent(),
+        }
+    }
+
+    fn get_child(&self) -> Result<Option<u64>> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_child(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_child(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_child(),
+        }
+    }
+}
+
+impl GetOutputs for Backend {
+    fn get_outputs(&self) -> Result<Outputs> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_outputs(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_outputs(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_outputs(),
+        }
+    }
+}
+
+impl GetWorkspaces for Backend {
+    fn get_workspaces(&self) -> Result<Workspaces> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref i3) => i3.get_workspaces(),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref wmctl) => wmctl.get_workspaces(),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref xcb) => xcb.get_workspaces(),
+        }
+    }
+}
+
 impl SetFocus for Backend {
-    fn set_focus(& mut self, id: &u64) {
+    fn set_focus(&mut self, id: &u64) -> Result<()> {
         match self.used_backend {
             #[cfg(feature = "i3")]
             UsedBackend::I3(ref mut i3) => i3.set_focus(id),
@@ -67,3 +168,77 @@ impl SetFocus for Backend {
         }
     }
 }
+
+impl ShowScratchpad for Backend {
+    fn show_scratchpad(&mut self, id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.show_scratchpad(id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.show_scratchpad(id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.show_scratchpad(id),
+        }
+    }
+
+    fn hide_scratchpad(&mut self, id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.hide_scratchpad(id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.hide_scratchpad(id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.hide_scratchpad(id),
+        }
+    }
+}
+
+impl LeaveFullscreen for Backend {
+    fn leave_fullscreen(&mut self, id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.leave_fullscreen(id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.leave_fullscreen(id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.leave_fullscreen(id),
+        }
+    }
+}
+
+impl MoveWindow for Backend {
+    fn swap_windows(&mut self, id: &u64, target_id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.swap_windows(id, target_id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.swap_windows(id, target_id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.swap_windows(id, target_id),
+        }
+    }
+
+    fn move_window(&mut self, id: &u64, target_id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.move_window(id, target_id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.move_window(id, target_id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.move_window(id, target_id),
+        }
+    }
+}
+
+impl FocusWorkspace for Backend {
+    fn focus_workspace(&mut self, workspace_id: &u64) -> Result<()> {
+        match self.used_backend {
+            #[cfg(feature = "i3")]
+            UsedBackend::I3(ref mut i3) => i3.focus_workspace(workspace_id),
+            #[cfg(feature = "wmctl")]
+            UsedBackend::WmCtl(ref mut wmctl) => wmctl.focus_workspace(workspace_id),
+            #[cfg(feature = "xcb")]
+            UsedBackend::Xcb(ref mut xcb) => xcb.focus_workspace(workspace_id),
+        }
+    }
+}
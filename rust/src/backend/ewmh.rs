@@ -0,0 +1,179 @@
+//! Conventions of the EWMH specification shared by the backends of window managers speaking it.
+
+use crate::types::{Rect, Window, Windows, Workspace, Workspaces};
+
+/// Desktop of the windows shown on all of them, the sticky ones.
+pub const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
+/// Tells whether a window belongs to the current desktop. Windows without a desktop, or under
+/// window managers without desktops, are taken as shown.
+pub fn is_on_desktop(window_desktop: Option<u32>, current_desktop: Option<u32>) -> bool {
+    match (window_desktop, current_desktop) {
+        (Some(ALL_DESKTOPS), _) | (None, _) | (_, None) => true,
+        (Some(desktop), Some(current)) => desktop == current,
+    }
+}
+
+/// Makes workspaces of the desktops, identified by their index, numbered from 1 and named by
+/// `_NET_DESKTOP_NAMES` or their number when unnamed. Desktops have no position, only the current
+/// one is visible.
+pub fn desktop_workspaces(count: u32, current: u32, names: &[String]) -> Workspaces {
+    (0..count).map(|index| Workspace {
+        id: index as u64,
+        num: Some(index as usize + 1),
+        name: names.get(index as usize).filter(|n| !n.is_empty()).cloned()
+            .unwrap_or_else(|| (index + 1).to_string()),
+        rect: Rect { x: 0, y: 0, w: 0, h: 0 },
+        focused: index == current,
+        visible: index == current,
+    }).collect()
+}
+
+/// Makes tabs of the windows tabbed together with the focused one, where window managers tab
+/// windows without telling: those framed by the same rect, or of the same client group, like the
+/// windows of one application, as long as they overlap the frame of the focused one. Windows of
+/// one application side by side are not tabs. Tabs are ordered by their index in
+/// `_NET_CLIENT_LIST`, the order they were mapped in.
+/// Without tabs, the visible windows are taken as they come, in stacking order.
+/// All the slices go along with the windows, windows of other desktops can't be tabs.
+pub fn emulated_tabs(windows: &[Window], groups: &[Option<u64>], mapping: &[usize], on_desktop: &[bool], visibility: &[bool]) -> Windows {
+    if let Some(focused) = windows.iter().position(|w| w.focused) {
+        let rect = windows[focused].rect;
+        let group = groups[focused];
+        let mut tabs: Vec<(usize, &Window)> = windows.iter().enumerate()
+            .filter(|(i, w)| on_desktop[*i]
+                && (w.rect == rect || (group.is_some() && groups[*i] == group && overlap(&w.rect, &rect))))
+            .collect();
+        if tabs.len() > 1 {
+            tabs.sort_by_key(|(i, _)| mapping[*i]);
+            return tabs.into_iter().map(|(_, w)| w.clone()).collect();
+        }
+    }
+    windows.iter().zip(visibility.iter())
+        .filter(|(_, visible)| **visible)
+        .map(|(w, _)| w.clone())
+        .collect()
+}
+
+/// Tells whether the rects share some area, touching edges don't count.
+pub fn overlap(a: &Rect, b: &Rect) -> bool {
+    a.left() < b.right() && b.left() < a.right() && a.top() < b.bottom() && b.top() < a.bottom()
+}
+
+/// Extents of the sides of a window, as `_NET_FRAME_EXTENTS` and `_GTK_FRAME_EXTENTS` give them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Extents {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+}
+
+impl Extents {
+    /// Parses the left, right, top and bottom extents, windows without them have none.
+    pub fn parse(value: &[u32]) -> Self {
+        match value {
+            [left, right, top, bottom, ..] =>
+                Extents { left: *left as i32, right: *right as i32, top: *top as i32, bottom: *bottom as i32 },
+            _ => Extents::default(),
+        }
+    }
+}
+
+/// Grows the rect of a client window to the frame the window manager decorates it with, and
+/// shrinks it by the invisible shadows of decorations drawn by the client itself, so that
+/// neighbouring windows share their edges.
+pub fn outer_frame(rect: Rect, frame: Extents, client_side: Extents) -> Rect {
+    Rect {
+        x: rect.x - frame.left + client_side.left,
+        y: rect.y - frame.top + client_side.top,
+        w: rect.w + frame.left + frame.right - client_side.left - client_side.right,
+        h: rect.h + frame.top + frame.bottom - client_side.top - client_side.bottom,
+    }
+}
+
+/// Parses the null separated names of `_NET_DESKTOP_NAMES`.
+pub fn parse_desktop_names(value: &[u8]) -> Vec<String> {
+    let value = value.strip_suffix(&[0]).unwrap_or(value);
+    if value.is_empty() {
+        return Vec::new();
+    }
+    value.split(|b| *b == 0).map(|name| String::from_utf8_lossy(name).to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_on_desktop() {
+        assert!(is_on_desktop(Some(1), Some(1)));
+        assert!(!is_on_desktop(Some(0), Some(1)));
+        assert!(is_on_desktop(Some(ALL_DESKTOPS), Some(1)));
+        assert!(is_on_desktop(None, Some(1)));
+        assert!(is_on_desktop(Some(2), None));
+    }
+
+    #[test]
+    fn test_desktop_workspaces() {
+        let workspaces = desktop_workspaces(3, 1, &["web".to_string(), String::new()]);
+        let names: Vec<&str> = workspaces.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "2", "3"]);
+        assert_eq!(workspaces.iter().position(|w| w.focused), Some(1));
+        assert_eq!(workspaces[2].id, 2);
+        assert_eq!(workspaces[2].num, Some(3));
+    }
+
+    fn window(id: u64, x: i32, focused: bool) -> Window {
+        Window { id, rect: Rect { x, y: 0, w: 100, h: 100 }, focused, ..Default::default() }
+    }
+
+    fn ids(windows: &Windows) -> Vec<u64> {
+        windows.iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn test_emulated_tabs() {
+        // Stacked from the top: the focused window, a tab behind it, another desktop, a neighbour.
+        // Ids don't tell the order of mapping, the tab behind was mapped first.
+        let windows = vec![window(2, 0, true), window(3, 0, false), window(1, 0, false), window(4, 100, false)];
+        let mapping = [1, 0, 2, 3];
+        let visibility = [true, false, false, true];
+        let tabs = emulated_tabs(&windows, &[None; 4], &mapping, &[true, true, false, true], &visibility);
+        assert_eq!(ids(&tabs), vec![3, 2]);
+
+        // Windows of the same application are tabs where they overlap the focused one
+        let mut windows = windows;
+        windows.push(Window { rect: Rect { x: 50, y: 50, w: 100, h: 100 }, ..window(5, 0, false) });
+        let mapping = [1, 0, 2, 4, 3];
+        let groups = [Some(7), None, None, Some(7), Some(7)];
+        let tabs = emulated_tabs(&windows, &groups, &mapping, &[true, false, false, true, true], &[true; 5]);
+        assert_eq!(ids(&tabs), vec![2, 5]);
+
+        // Side by side, they are not, and without tabs the visible windows are in stacking order
+        let tabs = emulated_tabs(&windows[..4], &groups[..4], &mapping[..4], &[true, false, false, true], &visibility);
+        assert_eq!(ids(&tabs), vec![2, 4]);
+        let windows = vec![window(3, 0, false), window(4, 100, false)];
+        assert_eq!(ids(&emulated_tabs(&windows, &[None; 2], &[1, 0], &[true; 2], &[true; 2])), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_outer_frame() {
+        let rect = Rect { x: 102, y: 124, w: 796, h: 674 };
+        let frame = Extents::parse(&[2, 2, 24, 2]);
+        assert_eq!(outer_frame(rect, frame, Extents::default()), Rect { x: 100, y: 100, w: 800, h: 700 });
+
+        let shadowed = Rect { x: 80, y: 80, w: 840, h: 740 };
+        let shadows = Extents::parse(&[20, 20, 20, 20]);
+        assert_eq!(outer_frame(shadowed, Extents::default(), shadows), Rect { x: 100, y: 100, w: 800, h: 700 });
+
+        assert_eq!(Extents::parse(&[1, 2]), Extents::default());
+    }
+
+    #[test]
+    fn test_parse_desktop_names() {
+        assert_eq!(parse_desktop_names(b"web\0code\0"), vec!["web".to_string(), "code".to_string()]);
+        assert_eq!(parse_desktop_names(b"web\0\0mail"), vec!["web".to_string(), String::new(), "mail".to_string()]);
+        assert!(parse_desktop_names(b"").is_empty());
+    }
+}
@@ -1,19 +1,25 @@
 use crate::backend::traits::*;
-use crate::logging::ResultExt;
-use crate::logging::OptionExt;
+use crate::error::{Error, Result};
 use crate::logging;
-use crate::types::Windows;
-use super::client::{Client, Request};
-use super::json::Node;
+use crate::types::{Outputs, TabLevel, Windows, Workspaces};
+use super::client::{Client, Event, Request};
+use super::json::{self as i3json, CommandOutcome, Node, ShutdownEvent, WindowEvent};
 use crate::types::Window;
 
+use serde::de::DeserializeOwned;
 use serde_json as json;
+use std::path::Path;
 use std::process;
 use std::thread;
+use std::time::Duration;
 
 pub struct Backend {
     client: Client,
-    root: Node,
+    // Boxed, the tree would make the backend the largest of the backend variants
+    root: Box<Node>,
+    outputs: Vec<i3json::Output>,
+    workspaces: Vec<i3json::Workspace>,
+    socket_path: String,
 }
 
 fn get_sock_path(executable: &str) -> Option<String> {
@@ -29,46 +35,283 @@ fn get_sock_path_async() -> Option<String> {
     })
 }
 
+/// Finds the IPC socket of i3 or sway, announced in the environment of their sessions or told by
+/// their binaries. Returns `None` when neither of them is running.
+fn find_sock_path() -> Option<String> {
+    for variable in ["I3SOCK", "SWAYSOCK"] {
+        if let Some(path) = std::env::var(variable).ok().filter(|p| Path::new(p).exists()) {
+            logging::debug!("Found socket path in {}: {}", variable, path);
+            return Some(path);
+        }
+    }
+    let path = get_sock_path_async()?;
+    logging::debug!("Found socket path with --get-socketpath: {}", path);
+    Some(path)
+}
+
 impl Backend {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         // Establish a connection to the i3 IPC server and get the tree structure
-        let socket_path = get_sock_path_async()
-            .wanted("Failed to get socket path from i3 or sway").unwrap_or_default();
-        let mut client = Client::new(&socket_path.trim())
-            .expect_log("Failed to connect to i3 IPC server");
-        let root_string = client.request(Request::GetTree, "")
-            .expect_log("Failed to get i3 tree JSON");
-
-        // Parse the i3 tree to get the current workspace and window information
-        let root: Node = json::from_str(root_string.as_str())
-            .expect_log("Failed to convert i3 tree JSON to Node");
-        Self {
+        let socket_path = find_sock_path()
+            .ok_or(Error::Connection("No I3SOCK or SWAYSOCK, and neither i3 nor sway told their socket path".to_string()))?;
+        let mut client = Client::new(socket_path.trim())?;
+        let root = Self::fetch_tree(&mut client)?;
+        let outputs = Self::fetch(&mut client, Request::GetOutputs);
+        let workspaces = Self::fetch(&mut client, Request::GetWorkspaces);
+        Ok(Self {
             client,
             root,
+            outputs,
+            workspaces,
+            socket_path,
+        })
+    }
+
+    /// Fetches the tree, outputs and workspaces again over the existing connection, so a long
+    /// living backend can keep up with the changes in the window manager.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.root = Self::fetch_tree(&mut self.client)?;
+        self.outputs = Self::fetch(&mut self.client, Request::GetOutputs);
+        self.workspaces = Self::fetch(&mut self.client, Request::GetWorkspaces);
+        Ok(())
+    }
+
+    /// Opens a separate connection subscribed to the events that change the tree layout.
+    /// Events are delivered on the subscribed socket, so they can't share the request client.
+    pub fn subscribe(&self) -> Result<Events> {
+        let socket_path = self.socket_path.trim().to_owned();
+        let client = Events::connect(&socket_path)?;
+        Ok(Events { client, socket_path })
+    }
+
+    /// Connects the requests again, after i3 restarted in place and closed the previous
+    /// connection. The tree is left as it is until the next refresh.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.client = Client::new(self.socket_path.trim())?;
+        Ok(())
+    }
+
+    /// Runs the i3 command and checks that every part of it succeeded.
+    fn command(&mut self, payload: &str) -> Result<()> {
+        let response = self.client.request(Request::Command, payload)?;
+        let outcomes = json::from_str::<Vec<CommandOutcome>>(response.as_str())
+            .map_err(|e| Error::Protocol(format!("Failed to parse command response: {}", e)))?;
+        match outcomes.iter().find(|o| !o.success) {
+            Some(outcome) => Err(Error::Command(format!("Command failed: {}",
+                outcome.error.as_deref().unwrap_or("unknown error")))),
+            None => Ok(()),
+        }
+    }
+
+    fn fetch_tree(client: &mut Client) -> Result<Box<Node>> {
+        let root_string = client.request(Request::GetTree, "")?;
+
+        // Parse the i3 tree to get the current workspace and window information
+        json::from_str(root_string.as_str())
+            .map_err(|e| Error::Protocol(format!("Failed to convert i3 tree JSON to Node: {}", e)))
+    }
+
+    /// Fetches a list the tree doesn't tell, like outputs or workspaces. Window navigation works
+    /// without them, so failing to get them is not fatal.
+    fn fetch<T: DeserializeOwned>(client: &mut Client, request: Request) -> Vec<T> {
+        let list = client.request(request, "")
+            .and_then(|s| json::from_str(s.as_str()).map_err(|e| Error::Protocol(e.to_string())));
+        match list {
+            Ok(list) => list,
+            Err(e) => {
+                logging::warning!("Failed to get response for {:?}: {}", request, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Mark used to point i3 where to move a container, it's removed right after the move.
+const MOVE_MARK: &str = "_i3switch_move_target";
+
+/// How many times to try connecting again to i3 restarting in place, and how long to wait between
+/// the attempts.
+const RESTART_ATTEMPTS: u32 = 50;
+const RESTART_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stream of i3 events that make a cached tree outdated.
+pub struct Events {
+    client: Client,
+    socket_path: String,
+}
+
+impl Events {
+    /// Blocks until the next event is received.
+    /// Along with the event, returns the details of window events, which tell what changed and
+    /// for which window.
+    /// When i3 restarts in place, the events are subscribed again once it's back and the shutdown
+    /// event is returned, the tree and the request connection are outdated by then. Returns
+    /// `None` when i3 exits.
+    pub fn wait(&mut self) -> Result<Option<(Event, Option<WindowEvent>)>> {
+        let (event, payload) = self.client.receive_event()?;
+        match event {
+            Event::Window => Ok(Some((event, json::from_str::<WindowEvent>(payload.as_str()).ok()))),
+            Event::Shutdown => {
+                let restart = json::from_str::<ShutdownEvent>(payload.as_str())
+                    .is_ok_and(|shutdown| shutdown.change == "restart");
+                if !restart {
+                    return Ok(None);
+                }
+                logging::info!("i3 is restarting, waiting for it to come back.");
+                self.client = Self::reconnect(&self.socket_path)?;
+                Ok(Some((event, None)))
+            }
+            _ => Ok(Some((event, None))),
+        }
+    }
+
+    /// Connects to i3 and subscribes to the events that change the tree layout.
+    fn connect(socket_path: &str) -> Result<Client> {
+        let mut client = Client::new(socket_path)?;
+        client.subscribe(&[Event::Window, Event::Workspace, Event::Output, Event::Shutdown])?;
+        Ok(client)
+    }
+
+    /// Connects again to i3 restarting in place, which takes a moment to listen again.
+    fn reconnect(socket_path: &str) -> Result<Client> {
+        let mut attempts = 1;
+        loop {
+            thread::sleep(RESTART_INTERVAL);
+            match Self::connect(socket_path) {
+                Err(e) if attempts < RESTART_ATTEMPTS => {
+                    logging::debug!("i3 is not back yet: {}", e);
+                    attempts += 1;
+                }
+                result => return result,
+            }
         }
     }
 }
 
 impl GetTabs for Backend {
-    fn get_tabs(&self) -> Result<Windows, String> {
+    fn get_tabs(&self) -> Result<Windows> {
         let nodes = self.root.available_tabs();
         Ok(nodes.iter().map(|node| Window::from(*node)).collect())
     }
+
+    fn get_tabs_at(&self, level: TabLevel) -> Result<Windows> {
+        let nodes = self.root.available_tabs_at(level);
+        Ok(nodes.iter().map(|node| Window::from(*node)).collect())
+    }
 }
 
 impl GetVisible for Backend {
-    fn get_visible(&self) -> Result<Windows, String> {
+    fn get_visible(&self) -> Result<Windows> {
         let nodes = self.root.visible_nodes();
         Ok(nodes.iter().map(|node| Window::from(*node)).collect())
     }
 }
 
+impl GetGroups for Backend {
+    fn get_groups(&self) -> Result<Windows> {
+        let nodes = self.root.visible_groups();
+        Ok(nodes.iter().map(|node| node.group_window()).collect())
+    }
+}
+
+impl GetAll for Backend {
+    fn get_all(&self) -> Result<Windows> {
+        Ok(self.root.all_windows())
+    }
+}
+
+impl GetScratchpad for Backend {
+    fn get_scratchpad(&self) -> Result<Windows> {
+        let nodes = self.root.scratchpad_nodes();
+        Ok(nodes.iter().map(|node| Window::from(*node)).collect())
+    }
+}
+
+impl GetContainers for Backend {
+    fn get_parent(&self) -> Result<Option<u64>> {
+        Ok(self.root.focused_parent().map(|node| node.id))
+    }
+
+    fn get_child(&self) -> Result<Option<u64>> {
+        Ok(self.root.focused_child().map(|node| node.id))
+    }
+}
+
+impl GetOutputs for Backend {
+    fn get_outputs(&self) -> Result<Outputs> {
+        Ok(self.outputs.iter()
+            .filter(|output| output.active)
+            .map(crate::types::Output::from)
+            .collect())
+    }
+}
+
+impl GetWorkspaces for Backend {
+    fn get_workspaces(&self) -> Result<Workspaces> {
+        // Numbered workspaces come first in order of their numbers, like i3 switches through them
+        let mut workspaces: Vec<&i3json::Workspace> = self.workspaces.iter().collect();
+        workspaces.sort_by_key(|w| (w.num < 0, w.num));
+        Ok(workspaces.into_iter().map(crate::types::Workspace::from).collect())
+    }
+}
+
 impl SetFocus for Backend {
-    fn set_focus(& mut self, window_id: &u64) {
+    fn set_focus(&mut self, window_id: &u64) -> Result<()> {
         // Focus the window with the determined ID
         logging::info!("Focusing window with ID: {}", window_id);
-        let payload = format!("[con_id={}] focus", window_id);
-        self.client.request(Request::Command, &payload)
-            .expect_log("Failed to send focus command");
+        self.command(&format!("[con_id={}] focus", window_id))
+    }
+}
+
+impl ShowScratchpad for Backend {
+    fn show_scratchpad(&mut self, window_id: &u64) -> Result<()> {
+        logging::info!("Showing scratchpad window with ID: {}", window_id);
+        self.command(&format!("[con_id={}] scratchpad show", window_id))
+    }
+
+    fn hide_scratchpad(&mut self, window_id: &u64) -> Result<()> {
+        logging::info!("Hiding window with ID: {} in scratchpad", window_id);
+        self.command(&format!("[con_id={}] move scratchpad", window_id))
+    }
+}
+
+impl LeaveFullscreen for Backend {
+    fn leave_fullscreen(&mut self, window_id: &u64) -> Result<()> {
+        logging::info!("Leaving fullscreen of window with ID: {}", window_id);
+        self.command(&format!("[con_id={}] fullscreen disable", window_id))?;
+        // i3 replies once the command is done, the tree shows the windows behind it already
+        self.refresh()
+    }
+}
+
+impl MoveWindow for Backend {
+    fn swap_windows(&mut self, window_id: &u64, target_id: &u64) -> Result<()> {
+        logging::info!("Swapping window with ID: {} with window ID: {}", window_id, target_id);
+        let payload = format!("[con_id={}] swap container with con_id {}", window_id, target_id);
+        self.command(&payload)
+    }
+
+    fn move_window(&mut self, window_id: &u64, target_id: &u64) -> Result<()> {
+        // i3 can only move a container next to another one through a mark, so the target gets
+        // a temporary one.
+        logging::info!("Moving window with ID: {} next to window ID: {}", window_id, target_id);
+        let payload = format!(
+            "[con_id={target}] mark --add {mark}; \
+             [con_id={window}] move container to mark {mark}; \
+             [con_id={target}] unmark {mark}; \
+             [con_id={window}] focus",
+            window = window_id, target = target_id, mark = MOVE_MARK);
+        self.command(&payload)
+    }
+}
+
+impl FocusWorkspace for Backend {
+    fn focus_workspace(&mut self, workspace_id: &u64) -> Result<()> {
+        let name = self.workspaces.iter()
+            .find(|w| w.id == *workspace_id)
+            .map(|w| w.name.replace('\\', "\\\\").replace('"', "\\\""))
+            .ok_or(Error::Missing(format!("No workspace with ID: {}", workspace_id)))?;
+        logging::info!("Focusing workspace with ID: {}", workspace_id);
+        self.command(&format!("workspace --no-auto-back-and-forth \"{}\"", name))
     }
 }
@@ -1,8 +1,9 @@
-use std::io::{self, Read, Write};
+use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
 use std::convert::TryInto;
 use std::thread;
 
+use crate::error::{Error, Result};
 use crate::logging;
 
 /// Represents a client for communicating with the i3 IPC socket.
@@ -12,9 +13,10 @@ pub struct Client {
 
 impl Client {
     /// Creates a new `Client` instance that connects to the i3 IPC socket at the specified path.
-    pub fn new(socket_path: &str) -> io::Result<Self> {
+    pub fn new(socket_path: &str) -> Result<Self> {
         logging::info!("Connecting to i3 IPC socket at: {}", socket_path);
-        let socket = UnixStream::connect(socket_path)?;
+        let socket = UnixStream::connect(socket_path)
+            .map_err(|e| Error::Connection(format!("Failed to connect to '{}': {}", socket_path, e)))?;
         Ok(Client { socket })
     }
 
@@ -22,15 +24,15 @@ impl Client {
     /// This function blocks until a response of the expected type is received or an error occurs,
     /// for example if the socket is closed or an unexpected response type is received.
     /// It will also fail if unable to send the request or if the response cannot be parsed.
-    pub fn request(&mut self, request_type: Request, payload: &str) -> io::Result<String> {
-        let receive_thread: thread::JoinHandle<io::Result<String>>;
+    pub fn request(&mut self, request_type: Request, payload: &str) -> Result<String> {
+        let receive_thread: thread::JoinHandle<Result<String>>;
         let return_type = Response::from(request_type);
         if let Ok(mut receive_socket) = self.socket.try_clone() {
             receive_thread = std::thread::spawn(move || {
                 Client::receive_unbound(&mut receive_socket, return_type)
             });
         } else {
-            return Err(io::Error::new(io::ErrorKind::Other, "Failed to clone socket"));
+            return Err(Error::Connection("Failed to clone socket".to_string()));
         }
 
         let packed_request = pack(request_type, payload);
@@ -38,13 +40,55 @@ impl Client {
         self.socket.flush()?;
         logging::info!("Sent request: {:?} with payload: {}", request_type, payload);
 
-        receive_thread.join().map_err(|_| io::Error::new(io::ErrorKind::Other, "Thread panicked"))?
+        receive_thread.join().map_err(|_| Error::Connection("Receiving thread panicked".to_string()))?
+    }
+
+    /// Subscribes to the given events, after which they can be received with `receive_event`.
+    /// i3 delivers events on the same socket as responses, so a subscribed client should not be
+    /// used for other requests.
+    pub fn subscribe(&mut self, events: &[Event]) -> Result<()> {
+        let names: Vec<String> = events.iter().map(|e| format!("\"{}\"", e.name())).collect();
+        let payload = format!("[{}]", names.join(","));
+        let response = self.request(Request::Subscribe, &payload)?;
+        if !response.contains("true") {
+            return Err(Error::Command(format!("Subscription rejected: {}", response)));
+        }
+        Ok(())
+    }
+
+    /// Receives the next event the client is subscribed to.
+    /// This function blocks until an event is received or the socket is closed.
+    pub fn receive_event(&mut self) -> Result<(Event, String)> {
+        loop {
+            let mut header = [0u8; std::mem::size_of::<Header>()];
+            self.socket.read_exact(&mut header)?;
+
+            let header: Header = Header::from_bytes(&header);
+            if &header.magic != b"i3-ipc" {
+                return Err(Error::Protocol("Invalid magic number".to_string()));
+            }
+            let mut payload = vec![0u8; header.payload_size as usize];
+            self.socket.read_exact(&mut payload)?;
+
+            let received_type = header.msg_type;
+            match Event::from_type(received_type) {
+                Some(event) => {
+                    logging::debug!("Received event: {:?}, with payload size: {}", event, payload.len());
+                    let payload = String::from_utf8(payload)
+                        .map_err(|e| Error::Protocol(e.to_string()))?;
+                    return Ok((event, payload));
+                }
+                None => {
+                    logging::warning!("Received unexpected message type: {:?}", received_type);
+                }
+            }
+        }
     }
 
     /// Receives a response from the i3 IPC socket.
     /// This function blocks until a response of the expected type is received or an error occurs,
     /// for example if the socket is closed or an unexpected response type is received.
-    fn receive_unbound(socket: &mut UnixStream, expected_type: Response) -> io::Result<String> {
+    fn receive_unbound(socket: &mut UnixStream, expected_type: Response) -> Result<String> {
         let expected_type = expected_type as u32;
         logging::debug!("Receiving started for response type: {:?}", expected_type);
         loop {
@@ -53,7 +97,7 @@ impl Client {
 
             let header: Header = Header::from_bytes(&header);
             if &header.magic != b"i3-ipc" {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic number"));
+                return Err(Error::Protocol("Invalid magic number".to_string()));
             }
             // Since we read the header, we have to read the payload.
             let mut payload = vec![0u8; header.payload_size as usize];
@@ -63,7 +107,7 @@ impl Client {
             if received_type == expected_type {
                 logging::debug!("Received response: {:?}, with payload size: {}", received_type, payload.len());
                 logging::debug!("Receiving finished for response type: {:?}", expected_type);
-                return String::from_utf8(payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+                return String::from_utf8(payload).map_err(|e| Error::Protocol(e.to_string()));
             }
             logging::warning!("Received unexpected response type: {:?}, expected: {:?}", received_type, expected_type);
         }
@@ -128,8 +172,59 @@ impl From<Request> for Response {
     }
 }
 
+/// Shamelessly copied from i3ipc.h
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Workspace       = 0,
+    Output          = 1,
+    Mode            = 2,
+    Window          = 3,
+    BarconfigUpdate = 4,
+    Binding         = 5,
+    Shutdown        = 6,
+    Tick            = 7,
+}
+
+/// Events are told apart from responses by the highest bit of the message type.
+const EVENT_MASK: u32 = 1 << 31;
+
+impl Event {
+    /// Returns the event for a received message type, if the message is an event.
+    fn from_type(msg_type: u32) -> Option<Self> {
+        if msg_type & EVENT_MASK == 0 {
+            return None;
+        }
+        match msg_type & !EVENT_MASK {
+            0 => Some(Event::Workspace),
+            1 => Some(Event::Output),
+            2 => Some(Event::Mode),
+            3 => Some(Event::Window),
+            4 => Some(Event::BarconfigUpdate),
+            5 => Some(Event::Binding),
+            6 => Some(Event::Shutdown),
+            7 => Some(Event::Tick),
+            _ => None,
+        }
+    }
+
+    /// Returns the name used to subscribe to the event.
+    fn name(&self) -> &'static str {
+        match self {
+            Event::Workspace       => "workspace",
+            Event::Output          => "output",
+            Event::Mode            => "mode",
+            Event::Window          => "window",
+            Event::BarconfigUpdate => "barconfig_update",
+            Event::Binding         => "binding",
+            Event::Shutdown        => "shutdown",
+            Event::Tick            => "tick",
+        }
+    }
+}
+
 /// Used to pack and unpack the IPC message header.
-#[repr(packed)]
+#[repr(C, packed)]
 struct Header {
     magic: [u8; 6],
     payload_size: u32,
@@ -139,7 +234,7 @@ struct Header {
 impl Header {
     fn new(payload_size: u32, msg_type: u32) -> Self {
         Header {
-            magic: b"i3-ipc".clone(),
+            magic: *b"i3-ipc",
             payload_size,
             msg_type,
         }
@@ -190,6 +285,16 @@ mod tests {
         assert_eq!(bytes, parsed_bytes);
     }
 
+    /// Test that events are told apart from responses by the message type.
+    #[test]
+    fn test_event_from_type() {
+        assert_eq!(Event::from_type(EVENT_MASK), Some(Event::Workspace));
+        assert_eq!(Event::from_type(EVENT_MASK | 3), Some(Event::Window));
+        assert_eq!(Event::from_type(EVENT_MASK | 6), Some(Event::Shutdown));
+        assert_eq!(Event::from_type(EVENT_MASK | 100), None);
+        assert_eq!(Event::from_type(Request::GetTree as u32), None);
+    }
+
     /// Test the packing of a Header and payload into a byte vector.
     #[test]
     fn test_pack() {
@@ -1,9 +1,12 @@
 use crate::types::Rect;
+use crate::types::Properties;
 use crate::types::Window;
+use crate::types::TabLevel;
 use crate::logging;
 
 use serde;
 use serde::Deserialize;
+use std::fmt;
 
 /// This enum represents the layout type of a node in a window manager's tree structure.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -26,6 +29,89 @@ pub struct Node {
     pub rect: Rect,
     pub focus: Vec<u64>,
     pub focused: bool,
+    #[serde(default)]
+    pub urgent: bool,
+    #[serde(default)]
+    pub window_properties: Option<WindowProperties>,
+    #[serde(default)]
+    pub app_id: Option<String>,
+    #[serde(default)]
+    pub marks: Vec<String>,
+    #[serde(default)]
+    pub window_type: Option<String>,
+    /// Whether sway shows the window, i3 doesn't tell.
+    #[serde(default)]
+    pub visible: Option<bool>,
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// Not fullscreen 0, fullscreen on its output 1, or on all outputs 2.
+    #[serde(default)]
+    pub fullscreen_mode: u8,
+    /// Whether the container came from the scratchpad, `none` if it didn't.
+    #[serde(default)]
+    pub scratchpad_state: Option<String>,
+}
+
+/// Name of the hidden workspace keeping the scratchpad windows.
+const SCRATCHPAD: &str = "__i3_scratch";
+
+/// Fullscreen mode of a container covering its output.
+const FULLSCREEN_OUTPUT: u8 = 1;
+/// Fullscreen mode of a container covering all outputs.
+const FULLSCREEN_GLOBAL: u8 = 2;
+
+/// Properties of X11 windows, native wayland windows in sway don't have them.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowProperties {
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Result of a single i3 command, a response contains one for each command in the payload.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutcome {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// The part of i3 window event that tells which container the change is about.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowEvent {
+    pub change: String,
+    pub container: EventContainer,
+}
+
+/// The i3 shutdown event, which tells whether i3 exits or restarts in place.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownEvent {
+    pub change: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EventContainer {
+    pub id: u64,
+}
+
+/// An output as reported by i3, inactive outputs don't show any workspace.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub name: String,
+    pub active: bool,
+    pub rect: Rect,
+}
+
+/// A workspace as reported by i3, named workspaces without a number have `num` of -1.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub id: u64,
+    pub num: i32,
+    pub name: String,
+    pub visible: bool,
+    pub focused: bool,
+    pub rect: Rect,
 }
 
 impl Node {
@@ -37,73 +123,217 @@ impl Node {
     /// Finds all available tabs in the node tree, relevant for current focus.
     /// Returns a vector of most recently focused nodes for each tab.
     pub fn available_tabs(&self) -> Vec<&Node> {
-        if let Some(subnode) = self.find_deepest_focused_tabbed() {
+        self.available_tabs_at(TabLevel::Inner(0))
+    }
+
+    /// Finds the tabs of the tabbed or stacked container at the level, among the ones nested on
+    /// the path to the focus.
+    /// Returns a vector of most recently focused nodes for each tab.
+    pub fn available_tabs_at(&self, level: TabLevel) -> Vec<&Node> {
+        if let Some(subnode) = self.find_focused_tabbed(level) {
             return subnode.nodes.iter().map(|tab| tab.find_deepest_focused().unwrap_or(tab)).collect();
         }
-        logging::info!("No available tabs found in the provided node.");
+        logging::info!("No available tabs found in the provided node at {:?}.", level);
         vec![]
     }
 
+    /// Finds the container enclosing the focused node. Containers end with the workspace, and
+    /// floating windows are not enclosed by any.
+    pub fn focused_parent(&self) -> Option<&Node> {
+        let path = self.focused_path()?;
+        let parent = *path.iter().rev().nth(1)?;
+        if ["con", "workspace"].contains(&parent.type_.as_str()) && !parent.is_content() {
+            Some(parent)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the most recently focused child of the focused container, by its `focus` array.
+    pub fn focused_child(&self) -> Option<&Node> {
+        let focused = *self.focused_path()?.last()?;
+        let focus_id = focused.focus.first()?;
+        focused.nodes.iter().chain(focused.floating_nodes.iter())
+            .find(|n| n.id == *focus_id)
+    }
+
     /// Finds all visible nodes in the node tree.
     /// Nodes are considered visible if they are on a visible workspace, not unfocused tab,
-    /// and have a non-zero rectangle size.
-    pub fn visible_nodes<'a>(&'a self) -> Vec<&'a Node> {
+    /// not behind a fullscreen container, and have a non-zero rectangle size, or sway says they
+    /// are visible.
+    pub fn visible_nodes(&self) -> Vec<&Node> {
+        self.collect_visible(false)
+    }
+
+    /// Finds all visible nodes like `visible_nodes`, but keeps switchable tabbed and stacked
+    /// containers whole, as they are seen on the screen as a single window.
+    pub fn visible_groups(&self) -> Vec<&Node> {
+        self.collect_visible(true)
+    }
+
+    /// Returns the window representing the node as a group. The group takes the place of the
+    /// whole container, but focuses its most recently focused window.
+    pub fn group_window(&self) -> Window {
+        let child = self.find_deepest_focused().unwrap_or(self);
+        Window {
+            id: child.id,
+            rect: self.rect,
+            focused: child.focused,
+            floating: self.is_floating(),
+            urgent: child.urgent,
+            fullscreen: self.is_fullscreen() || child.is_fullscreen(),
+            properties: child.properties(),
+        }
+    }
+
+    /// Finds all windows in the node tree, including the ones on hidden workspaces and unfocused
+    /// tabs. i3 wraps floating windows in floating containers without their properties, the
+    /// windows inside are taken instead, as focus events report them, and kept floating.
+    pub fn all_windows(&self) -> Vec<Window> {
+        self.collect_all(false).into_iter()
+            .map(|(node, floating)| {
+                let window = Window::from(node);
+                Window { floating: floating || window.floating, ..window }
+            })
+            .collect()
+    }
+
+    /// Finds all window nodes of the scratchpad, the hidden ones and the ones shown from it.
+    pub fn scratchpad_nodes(&self) -> Vec<&Node> {
+        self.collect_scratchpad(false)
+    }
+
+    /// Returns what the node is known as. The title falls back to the node name, which is all
+    /// there is for native wayland windows.
+    pub fn properties(&self) -> Properties {
+        let window = self.window_properties.as_ref();
+        Properties {
+            class: window.and_then(|w| w.class.clone()),
+            instance: window.and_then(|w| w.instance.clone()),
+            title: window.and_then(|w| w.title.clone()).or_else(|| self.name.clone()),
+            app_id: self.app_id.clone(),
+            marks: self.marks.clone(),
+            window_type: self.window_type.clone(),
+            shell: self.shell.clone(),
+            pid: self.pid,
+        }
+    }
+
+    // ---------------
+    // Private methods
+    // ---------------
+
+    /// Collects visible nodes, optionally stopping at switchable tabbed and stacked containers.
+    fn collect_visible(&self, keep_groups: bool) -> Vec<&Node> {
         logging::debug!("V Iterated {}", self.to_string());
+        if self.is_scratchpad_workspace() {
+            // The focused workspace of the hidden output, i3 keeps the rectangles of its windows
+            return vec![];
+        }
         if self.is_leaf() {
             if self.is_invisible() {
                 return vec![];
             }
             return vec![self];
         }
+        if keep_groups && self.is_switchable_tabbed() {
+            return vec![self];
+        }
+        // i3 keeps the rectangles of the windows behind a fullscreen container
+        let mode = match self.type_.as_str() {
+            "root" => Some(FULLSCREEN_GLOBAL),
+            "workspace" => Some(FULLSCREEN_OUTPUT),
+            _ => None,
+        };
+        if let Some(fullscreen) = mode.and_then(|mode| self.find_fullscreen(mode)) {
+            return fullscreen.collect_visible(keep_groups);
+        }
 
         let layout = self.get_layout();
         match layout {
             Layout::AllVisible => {
-                let mut nodes: Vec<&'a Node> = vec![];
-                nodes.extend(self.floating_nodes.iter());
+                let mut nodes: Vec<&Node> = vec![];
+                // Sway hides floating windows behind a fullscreen one
+                nodes.extend(self.floating_nodes.iter().filter(|n| !n.is_invisible()));
                 self.nodes.iter().for_each(|subnode| {
-                    nodes.extend(subnode.visible_nodes());
+                    nodes.extend(subnode.collect_visible(keep_groups));
                 });
-                return nodes;
+                nodes
             }
             Layout::OneVisible => {
-                let mut nodes: Vec<&'a Node> = vec![];
+                let mut nodes: Vec<&Node> = vec![];
                 if let Some(focused_node) = self.focused_subnode() {
-                    nodes.extend(focused_node.visible_nodes());
+                    nodes.extend(focused_node.collect_visible(keep_groups));
                 }
-                return nodes;
+                nodes
             }
             Layout::Skipped => vec![],
             Layout::Invalid => {
                 logging::error!("Invalid layout encountered: {:?}", layout);
-                return vec![]
+                vec![]
             }
         }
     }
 
-    // ---------------
-    // Private methods
-    // ---------------
-
     /// Checks if the node is a leaf node, meaning it has no subnodes and is a container.
     fn is_leaf(&self) -> bool {
         self.nodes.is_empty() &&
             (self.type_ == "con" || self.type_ == "floating_con")
     }
 
+    /// Collects window nodes down to the leaves, along with whether they are in a floating
+    /// container.
+    fn collect_all(&self, floating: bool) -> Vec<(&Node, bool)> {
+        if self.is_leaf() {
+            return vec![(self, floating)];
+        }
+        if self.get_layout() == Layout::Skipped {
+            return vec![];
+        }
+        let mut nodes: Vec<(&Node, bool)> = self.floating_nodes.iter()
+            .flat_map(|n| n.collect_all(true))
+            .collect();
+        self.nodes.iter().for_each(|subnode| {
+            nodes.extend(subnode.collect_all(floating));
+        });
+        nodes
+    }
+
+    /// Collects window nodes in the scratchpad, or under a container shown from it.
+    fn collect_scratchpad(&self, in_scratchpad: bool) -> Vec<&Node> {
+        let in_scratchpad = in_scratchpad || self.is_scratchpad_workspace() ||
+            self.scratchpad_state.as_deref().is_some_and(|state| state != "none");
+        if self.is_leaf() {
+            return if in_scratchpad { vec![self] } else { vec![] };
+        }
+        self.nodes.iter().chain(self.floating_nodes.iter())
+            .flat_map(|n| n.collect_scratchpad(in_scratchpad))
+            .collect()
+    }
+
+    /// Checks if the node is the workspace keeping the hidden scratchpad windows.
+    fn is_scratchpad_workspace(&self) -> bool {
+        self.type_ == "workspace" && self.name.as_deref() == Some(SCRATCHPAD)
+    }
+
     /// Checks if the node is a content node, which is a special type of node containing
-    /// workspaces.
+    /// workspaces. i3 has a content container in each output, sway puts the workspaces right in
+    /// the output.
     fn is_content(&self) -> bool {
-        self.type_ == "con" &&
-            self.name.is_some() &&
-            self.name.as_ref().unwrap() == "content" &&
-            !self.is_leaf()
+        match self.type_.as_str() {
+            "con" => self.name.as_deref() == Some("content") && !self.is_leaf(),
+            "output" => self.nodes.iter().any(|n| n.type_ == "workspace"),
+            _ => false,
+        }
     }
 
-    /// Checks if the node is invisible, meaning it has a rectangle with zero width and height.
+    /// Checks if the node is invisible. Sway tells it, for i3 it means the node has a rectangle
+    /// with zero width and height. Sway keeps the rectangles of hidden windows.
     fn is_invisible(&self) -> bool {
-        self.rect.w == 0 &&
-            self.rect.h == 0
+        match self.visible {
+            Some(visible) => !visible,
+            None => self.rect.w == 0 && self.rect.h == 0,
+        }
     }
 
     /// Checks if the node is floating, meaning it is a floating container or has any floating
@@ -113,6 +343,17 @@ impl Node {
             self.floating_nodes.iter().any(|n| n.is_floating())
     }
 
+    /// Checks if the node is fullscreen, on its output or on all of them.
+    fn is_fullscreen(&self) -> bool {
+        self.fullscreen_mode != 0
+    }
+
+    /// Finds the node fullscreen in the given mode in the subtree, floating ones included.
+    fn find_fullscreen(&self, mode: u8) -> Option<&Node> {
+        self.nodes.iter().chain(self.floating_nodes.iter())
+            .find_map(|n| if n.fullscreen_mode == mode { Some(n) } else { n.find_fullscreen(mode) })
+    }
+
     /// Returns whether the node is a tabbed layout that has multiple subnodes.
     fn is_switchable_tabbed(&self) -> bool {
         self.get_layout() == Layout::OneVisible &&
@@ -143,6 +384,42 @@ impl Node {
         }
     }
 
+    /// Finds the focused tabbed node at the level, counted from the deepest one.
+    fn find_focused_tabbed(&self, level: TabLevel) -> Option<&Node> {
+        match level {
+            TabLevel::Inner(0) => self.find_deepest_focused_tabbed(),
+            TabLevel::Inner(depth) => self.focused_tabbed_chain().into_iter().rev().nth(depth),
+            TabLevel::Outer => self.focused_tabbed_chain().into_iter().next(),
+        }
+    }
+
+    /// Collects the tabbed nodes with multiple subnodes on the path to the focus, from the
+    /// outermost one.
+    fn focused_tabbed_chain(&self) -> Vec<&Node> {
+        let mut chain = vec![];
+        let mut node = self;
+        while let Some(subnode) = node.focused_subnode() {
+            if node.is_switchable_tabbed() {
+                chain.push(node);
+            }
+            node = subnode;
+        }
+        chain
+    }
+
+    /// Finds the path from the node to the focused node, floating ones included.
+    fn focused_path(&self) -> Option<Vec<&Node>> {
+        if self.focused {
+            return Some(vec![self]);
+        }
+        self.nodes.iter().chain(self.floating_nodes.iter())
+            .find_map(|n| n.focused_path())
+            .map(|mut path| {
+                path.insert(0, self);
+                path
+            })
+    }
+
     /// Finds the deepest focused node in the tree.
     fn find_deepest_focused(&self) -> Option<&Node> {
         logging::debug!("F Iterated {}", self.to_string());
@@ -159,7 +436,7 @@ impl Node {
             ["stacked", "tabbed"].contains(&self.layout.as_str())  {
             Layout::OneVisible
         } else if ["splith", "splitv", "output"].contains(&self.layout.as_str()) &&
-                ["workspace", "root", "output", "con"].contains(&self.type_.as_str()) {
+                ["workspace", "root", "output", "con", "floating_con"].contains(&self.type_.as_str()) {
             Layout::AllVisible
         } else if self.layout == "dockarea" {
             Layout::Skipped
@@ -171,20 +448,45 @@ impl Node {
 
 impl From<&Node> for Window {
     fn from(node: &Node) -> Self {
-        let id = node.id as u64;
-        let rect = node.rect.clone();
+        let id = node.id;
+        let rect = node.rect;
         let floating = node.is_floating();
         let focused = node.focused;
+        let urgent = node.urgent;
+        let fullscreen = node.is_fullscreen();
+        let properties = node.properties();
 
-        Window { id, rect, focused, floating, }
+        Window { id, rect, focused, floating, urgent, fullscreen, properties }
     }
 }
 
-impl ToString for Node {
-    fn to_string(&self) -> String {
-        let result = format!("Node id={} type={} layout={}",
-            self.id, self.type_, self.layout);
-        result
+impl From<&Output> for crate::types::Output {
+    fn from(output: &Output) -> Self {
+        crate::types::Output { name: output.name.clone(), rect: output.rect }
+    }
+}
+
+impl From<&Workspace> for crate::types::Workspace {
+    fn from(workspace: &Workspace) -> Self {
+        crate::types::Workspace {
+            id: workspace.id,
+            num: usize::try_from(workspace.num).ok(),
+            name: workspace.name.clone(),
+            rect: workspace.rect,
+            focused: workspace.focused,
+            visible: workspace.visible,
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Node id={} type={} layout={}",
+            self.id, self.type_, self.layout)?;
+        match &self.shell {
+            Some(shell) => write!(f, " shell={}", shell),
+            None => Ok(()),
+        }
     }
 }
 
@@ -221,6 +523,263 @@ mod tests {
         assert_eq!(nodes.len(), 4);
     }
 
+    /// Tests for visible groups extraction.
+    /// We expect tabbed containers to be returned whole, represented by their most recently
+    /// focused window.
+    #[test]
+    fn test_visible_groups() {
+        let root: Node = read_json("jsons/tabbed_column.json");
+        let ids: Vec<u64> = root.visible_nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![2, 6, 7]);
+
+        let groups = root.visible_groups();
+        let ids: Vec<u64> = groups.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+
+        let window = groups[1].group_window();
+        assert_eq!(window.id, 7);
+        assert_eq!(window.rect, Rect { x: 100, y: 0, w: 100, h: 100 });
+        assert!(!window.focused);
+
+        let window = groups[0].group_window();
+        assert_eq!(window.id, 2);
+        assert!(window.focused);
+    }
+
+    /// Tests for all nodes extraction.
+    /// We expect the function to return windows on hidden workspaces and behind tabs as well.
+    #[test]
+    fn test_all_nodes() {
+        let node: Node = read_json("jsons/root_with_several_nodes.json");
+        let mut ids: Vec<u64> = node.all_windows().iter().map(|w| w.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![5, 6, 11, 13, 14, 20]);
+    }
+
+    /// Tests for window properties extraction.
+    /// We expect the title to fall back to the node name for windows without X11 properties.
+    #[test]
+    fn test_properties() {
+        let root: Node = read_json("jsons/window_properties.json");
+        let windows = root.all_windows();
+        assert_eq!(windows.len(), 4);
+
+        let browser = &windows[1].properties;
+        assert_eq!(browser.class.as_deref(), Some("firefox"));
+        assert_eq!(browser.instance.as_deref(), Some("Navigator"));
+        assert_eq!(browser.title.as_deref(), Some("Docs - Mozilla Firefox"));
+        assert_eq!(browser.app_id, None);
+
+        let editor = &windows[2].properties;
+        assert_eq!(editor.class, None);
+        assert_eq!(editor.app_id.as_deref(), Some("org.gnome.TextEditor"));
+        assert_eq!(editor.title.as_deref(), Some("notes.md"));
+        assert_eq!(editor.marks, vec!["notes".to_string()]);
+
+        let urgent: Vec<u64> = windows.iter().filter(|w| w.urgent).map(|w| w.id).collect();
+        assert_eq!(urgent, vec![22]);
+    }
+
+    /// Tests for the tree of sway.
+    /// We expect sway's visible flag to hide the scratchpad and unfocused tabs, and its outputs
+    /// to hold the workspaces without a content container.
+    #[test]
+    fn test_sway_tree() {
+        let root: Node = read_json("jsons/sway_tree.json");
+
+        let ids: Vec<u64> = root.visible_nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![15, 12, 14]);
+        let ids: Vec<u64> = root.visible_groups().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![15, 11, 14]);
+        let ids: Vec<u64> = root.available_tabs().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![12, 13]);
+        let mut ids: Vec<u64> = root.all_windows().iter().map(|w| w.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![5, 12, 13, 14, 15, 21]);
+
+        let windows: Vec<Window> = root.visible_nodes().iter().map(|n| Window::from(*n)).collect();
+        assert!(windows[0].floating);
+        assert_eq!(windows[0].properties.app_id.as_deref(), Some("pavucontrol"));
+        let xwayland = &windows[1].properties;
+        assert_eq!(xwayland.class.as_deref(), Some("firefox"));
+        assert_eq!(xwayland.shell.as_deref(), Some("xwayland"));
+        assert_eq!(xwayland.pid, Some(1012));
+        let native = &windows[2].properties;
+        assert_eq!(native.class, None);
+        assert_eq!(native.app_id.as_deref(), Some("foot"));
+        assert_eq!(native.shell.as_deref(), Some("xdg_shell"));
+    }
+
+    /// Tests for a fullscreen window in sway.
+    /// We expect only the fullscreen window to be visible, sway hides the tiled and floating
+    /// windows behind it, but keeps their rectangles.
+    #[test]
+    fn test_sway_fullscreen() {
+        let root: Node = read_json("jsons/sway_fullscreen.json");
+        let nodes = root.visible_nodes();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id, 5);
+        assert_eq!(nodes[0].fullscreen_mode, 1);
+    }
+
+    /// Tests for a fullscreen container in i3.
+    /// We expect the windows behind it to be hidden, although i3 keeps their rectangles.
+    #[test]
+    fn test_fullscreen() {
+        let mut root: Node = read_json("jsons/2node_splith.json");
+        root.type_ = "workspace".to_string();
+        root.nodes[1].fullscreen_mode = 1;
+        let windows: Vec<Window> = root.visible_nodes().iter().map(|n| Window::from(*n)).collect();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].id, 3);
+        assert!(windows[0].fullscreen);
+
+        // Global fullscreen hides the windows on the other workspaces as well
+        root.nodes[1].fullscreen_mode = 2;
+        assert_eq!(root.visible_nodes().len(), 2);
+        root.type_ = "root".to_string();
+        assert_eq!(root.visible_nodes().len(), 1);
+    }
+
+    /// Tests for scratchpad windows extraction.
+    /// We expect the hidden scratchpad windows and the shown ones, but only the shown ones to be
+    /// visible.
+    #[test]
+    fn test_scratchpad_nodes() {
+        let root: Node = read_json("jsons/scratchpad.json");
+        let ids: Vec<u64> = root.scratchpad_nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![32, 34, 36]);
+        assert!(root.scratchpad_nodes()[2].focused);
+
+        let ids: Vec<u64> = root.visible_nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![35, 11]);
+    }
+
+    /// Tests for all windows extraction on i3, which wraps floating windows in containers.
+    /// We expect the wrapped windows with their properties, floating, the scratchpad included.
+    #[test]
+    fn test_all_windows_floating() {
+        let root: Node = read_json("jsons/scratchpad.json");
+        let windows = root.all_windows();
+        let ids: Vec<u64> = windows.iter().map(|w| w.id).collect();
+        assert_eq!(ids, vec![32, 34, 36, 11]);
+
+        let ranger = &windows[2];
+        assert!(ranger.floating && ranger.focused);
+        assert_eq!(ranger.properties.class.as_deref(), Some("Alacritty"));
+        assert_eq!(ranger.properties.title.as_deref(), Some("ranger"));
+        assert!(!windows[3].floating);
+    }
+
+    struct Tree(Node);
+
+    impl crate::backend::traits::GetAll for Tree {
+        fn get_all(&self) -> crate::error::Result<crate::types::Windows> {
+            Ok(self.0.all_windows())
+        }
+    }
+
+    /// Tests raising floating i3 windows, which raise-or-run does before running anything.
+    /// We expect the window wrapped by a floating container to meet the criteria, instead of
+    /// another one being run.
+    #[test]
+    fn test_raise_floating() {
+        use crate::navigation::{self, Options};
+        use crate::types::Criteria;
+
+        let tree = Tree(read_json("jsons/scratchpad.json"));
+        let criteria = Criteria::parse("class=alacritty,title=ranger").unwrap();
+        assert_eq!(navigation::get_window_to_raise(&tree, &Options::default(), &criteria), Ok(Some(36)));
+        let criteria = Criteria::parse("title=htop").unwrap();
+        assert_eq!(navigation::get_window_to_raise(&tree, &Options::default(), &criteria), Ok(Some(32)));
+    }
+
+    /// Tests the focus history with floating i3 windows, recorded by the ids focus events report.
+    /// We expect them to be kept when the history is pruned of the windows that are gone.
+    #[test]
+    fn test_history_floating() {
+        use crate::history::History;
+        use crate::navigation::{self, Options};
+
+        let tree = Tree(read_json("jsons/scratchpad.json"));
+        let mut history = History::default();
+        for window_id in [11, 99, 36] {
+            history.focus(window_id, 16);
+        }
+        // The window 99 is gone
+        assert_eq!(navigation::get_previous_window(&tree, &Options::default(), &mut history), Ok(Some(11)));
+        history.focus(11, 16);
+        assert_eq!(navigation::get_previous_window(&tree, &Options::default(), &mut history), Ok(Some(36)));
+    }
+
+    /// Tests for focus event parsing.
+    /// We expect only the change and the container id to be required.
+    #[test]
+    fn test_window_event() {
+        let event: WindowEvent = serde_json::from_str(
+            r#"{"change": "focus", "container": {"id": 42, "name": "term", "focused": true}}"#)
+            .expect("Failed to parse window event");
+        assert_eq!(event.change, "focus");
+        assert_eq!(event.container.id, 42);
+    }
+
+    /// Tests for shutdown event parsing.
+    /// We expect the change to tell a restart from an exit.
+    #[test]
+    fn test_shutdown_event() {
+        let event: ShutdownEvent = serde_json::from_str(r#"{"change": "restart"}"#)
+            .expect("Failed to parse shutdown event");
+        assert_eq!(event.change, "restart");
+        let event: ShutdownEvent = serde_json::from_str(r#"{"change": "exit"}"#)
+            .expect("Failed to parse shutdown event");
+        assert_eq!(event.change, "exit");
+    }
+
+    /// Tests for outputs parsing.
+    /// We expect unknown fields to be ignored and inactive outputs to be kept.
+    #[test]
+    fn test_outputs() {
+        let content = std::fs::read_to_string("jsons/outputs.json")
+            .expect("Failed to read JSON file");
+        let outputs: Vec<Output> = serde_json::from_str(content.as_str())
+            .expect("Failed to parse outputs");
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[1].name, "HDMI-1");
+        assert!(outputs[1].active);
+        assert_eq!(outputs[1].rect, Rect { x: 1920, y: 0, w: 2560, h: 1440 });
+        assert!(!outputs[2].active);
+    }
+
+    /// Tests for workspaces parsing.
+    /// We expect named workspaces without a number to be parsed as well.
+    #[test]
+    fn test_workspaces() {
+        let content = std::fs::read_to_string("jsons/workspaces.json")
+            .expect("Failed to read JSON file");
+        let workspaces: Vec<Workspace> = serde_json::from_str(content.as_str())
+            .expect("Failed to parse workspaces");
+        assert_eq!(workspaces.len(), 4);
+        assert_eq!(workspaces[1].num, -1);
+        assert_eq!(workspaces[1].name, "mail");
+        assert!(workspaces[2].focused);
+        assert_eq!(workspaces[3].rect, Rect { x: 1920, y: 0, w: 2560, h: 1440 });
+        assert_eq!(crate::types::Workspace::from(&workspaces[1]).num, None);
+        assert_eq!(crate::types::Workspace::from(&workspaces[2]).num, Some(workspaces[2].num as usize));
+    }
+
+    /// Tests for command response parsing.
+    /// We expect an outcome for each command, with an error only for the failed ones.
+    #[test]
+    fn test_command_outcome() {
+        let outcomes: Vec<CommandOutcome> = serde_json::from_str(
+            r#"[{"success": true}, {"success": false, "parse_error": false, "error": "No such mark"}]"#)
+            .expect("Failed to parse command response");
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes[0].success);
+        assert!(!outcomes[1].success);
+        assert_eq!(outcomes[1].error.as_deref(), Some("No such mark"));
+    }
+
     /// Tests for extracting available tabs from a node.
     /// We expect the function to return a vector of leaf nodes that are focused of a tabbed
     /// layout, or none if there are no tabs.
@@ -233,6 +792,39 @@ mod tests {
         assert!(tabs.iter().any(|tab| tab.id == 5));
     }
 
+    /// Tests for extracting tabs of nested tabbed containers.
+    /// We expect the levels to count outwards from the innermost tabs, the tabs of outer levels
+    /// being represented by their most recently focused windows.
+    #[test]
+    fn test_available_tabs_at() {
+        let node: Node = read_json("jsons/nested_tabs.json");
+        let ids = |level| node.available_tabs_at(level).iter().map(|n| n.id).collect::<Vec<u64>>();
+        assert_eq!(ids(TabLevel::Inner(0)), vec![4, 5]);
+        assert_eq!(ids(TabLevel::Inner(1)), vec![4, 6]);
+        assert_eq!(ids(TabLevel::Outer), vec![4, 6]);
+        assert!(ids(TabLevel::Inner(2)).is_empty());
+    }
+
+    /// Tests for finding the parent and child of the focused node.
+    /// We expect the parent to be the enclosing container, and the child the remembered one of
+    /// the focused container.
+    #[test]
+    fn test_focused_parent_and_child() {
+        let mut node: Node = read_json("jsons/nested_tabs.json");
+        assert_eq!(node.focused_parent().map(|n| n.id), Some(3));
+        assert_eq!(node.focused_child().map(|n| n.id), None);
+
+        node.nodes[0].nodes[0].nodes[0].focused = false;
+        node.nodes[0].nodes[0].focused = true;
+        assert_eq!(node.focused_parent().map(|n| n.id), Some(2));
+        assert_eq!(node.focused_child().map(|n| n.id), Some(4));
+
+        node.nodes[0].nodes[0].focused = false;
+        node.focused = true;
+        assert_eq!(node.focused_parent().map(|n| n.id), None);
+        assert_eq!(node.focused_child().map(|n| n.id), Some(2));
+    }
+
     /// Tests for converting JSON nodes to windows.
     #[test]
     fn test_to_windows() {
@@ -289,6 +881,10 @@ mod tests {
         node.layout = "stacked".to_string();
         assert_eq!(node.get_layout(), Layout::OneVisible);
 
+        node.type_ = "floating_con".to_string();
+        node.layout = "splitv".to_string();
+        assert_eq!(node.get_layout(), Layout::AllVisible);
+
         node.type_ = "dockarea".to_string();
         node.layout = "dockarea".to_string();
         assert_eq!(node.get_layout(), Layout::Skipped);
@@ -300,7 +896,7 @@ mod tests {
 
     /// Tests for invisible node detection.
     /// We expect the function to return true for nodes that have a rectangle with zero width and
-    /// height.
+    /// height, unless sway tells the visibility.
     #[test]
     fn test_is_invisible_node() {
         let mut node: Node = read_json("jsons/empty_node.json");
@@ -311,6 +907,9 @@ mod tests {
         node.rect.w = 100;
         node.rect.h = 100;
         assert!(!node.is_invisible());
+
+        node.visible = Some(false);
+        assert!(node.is_invisible());
     }
 
     /// Tests for end node detection.
@@ -3,3 +3,5 @@ mod json;
 pub mod backend;
 
 pub use crate::backend::i3::backend::Backend;
+pub use crate::backend::i3::client::Event;
+pub use crate::backend::i3::json::WindowEvent;
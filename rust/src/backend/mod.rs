@@ -4,8 +4,11 @@ pub mod i3;
 pub mod wmctl;
 #[cfg(feature = "xcb")]
 pub mod xcb;
+#[cfg(any(feature = "xcb", feature = "wmctl"))]
+mod ewmh;
 
 pub mod traits;
+#[allow(clippy::module_inception)]
 pub mod backend;
 
 #[cfg(feature = "i3")]
@@ -1,13 +1,84 @@
-use crate::types::Windows;
+use crate::error::{Error, Result};
+use crate::types::{Outputs, TabLevel, Windows, Workspaces};
 
 pub trait GetTabs {
-    fn get_tabs(&self) -> Result<Windows, String>;
+    fn get_tabs(&self) -> Result<Windows>;
+
+    /// Gets the tabs of the container at the level, when tabbed or stacked containers are
+    /// nested. Backends without nested containers know just the innermost tabs.
+    fn get_tabs_at(&self, level: TabLevel) -> Result<Windows> {
+        match level {
+            TabLevel::Inner(0) => self.get_tabs(),
+            _ => Err(Error::Unsupported("Nested tabs not supported in this backend".to_string())),
+        }
+    }
 }
 
 pub trait GetVisible {
-    fn get_visible(&self) -> Result<Windows, String>;
+    fn get_visible(&self) -> Result<Windows>;
+}
+
+/// Gets visible windows where tabbed and stacked containers count as a single window, which
+/// focuses the most recently focused window of the container.
+pub trait GetGroups {
+    fn get_groups(&self) -> Result<Windows>;
+}
+
+/// Gets every window managed by the window manager, including the ones on hidden workspaces
+/// and behind tabs.
+pub trait GetAll {
+    fn get_all(&self) -> Result<Windows>;
+}
+
+/// Gets the active outputs, so that navigation can respect the edges of monitors.
+pub trait GetOutputs {
+    fn get_outputs(&self) -> Result<Outputs>;
+}
+
+/// Gets every workspace in the order the window manager would switch through them.
+pub trait GetWorkspaces {
+    fn get_workspaces(&self) -> Result<Workspaces>;
+}
+
+/// Gets the windows kept in the scratchpad, the hidden ones and the ones shown from it.
+pub trait GetScratchpad {
+    fn get_scratchpad(&self) -> Result<Windows>;
+}
+
+/// Gets the containers around the focused one, to focus them like windows.
+pub trait GetContainers {
+    /// Gets the container enclosing the focused window or container, up to the workspace.
+    fn get_parent(&self) -> Result<Option<u64>>;
+    /// Gets the most recently focused child of the focused container.
+    fn get_child(&self) -> Result<Option<u64>>;
 }
 
 pub trait SetFocus {
-    fn set_focus(& mut self, window_id: &u64);
+    fn set_focus(&mut self, window_id: &u64) -> Result<()>;
+}
+
+/// Takes windows out of fullscreen, so that the windows behind them can be navigated.
+pub trait LeaveFullscreen {
+    /// Leaves fullscreen of the window, the windows behind it are visible afterwards.
+    fn leave_fullscreen(&mut self, window_id: &u64) -> Result<()>;
+}
+
+/// Rearranges windows while keeping the focus on the window being moved.
+pub trait MoveWindow {
+    /// Swaps the window with the target window.
+    fn swap_windows(&mut self, window_id: &u64, target_id: &u64) -> Result<()>;
+    /// Moves the window next to the target window, into the target's parent container.
+    fn move_window(&mut self, window_id: &u64, target_id: &u64) -> Result<()>;
+}
+
+/// Brings windows up from the scratchpad and back.
+pub trait ShowScratchpad {
+    /// Shows the scratchpad window, focusing it.
+    fn show_scratchpad(&mut self, window_id: &u64) -> Result<()>;
+    /// Hides the window in the scratchpad.
+    fn hide_scratchpad(&mut self, window_id: &u64) -> Result<()>;
+}
+
+pub trait FocusWorkspace {
+    fn focus_workspace(&mut self, workspace_id: &u64) -> Result<()>;
 }
@@ -1,67 +1,109 @@
 use x11rb::connection::Connection;
-use x11rb::protocol::xproto::{ConnectionExt, EventMask};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, EventMask};
 use x11rb::rust_connection::RustConnection;
 use x11rb::protocol::xproto::ClientMessageEvent;
 
 use libwmctl::prelude::{windows, active, State};
+use crate::backend::ewmh;
 use crate::backend::traits::*;
-use crate::types::{Rect, Window, Windows};
+use crate::error::{Error, Result};
+use crate::logging;
+use crate::types::{Outputs, Properties, Rect, Window, Windows, Workspaces};
 
 pub struct Backend {
     windows: Windows,
     visibility: Vec<bool>,
+    on_desktop: Vec<bool>,
+    groups: Vec<Option<u64>>,
+    /// Index of the windows in `_NET_CLIENT_LIST`, the order libwmctl lists them in.
+    mapping: Vec<usize>,
 }
 
 impl Backend {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         let show_hidden = false;
         let wm_windows = windows(show_hidden)
-            .expect("Failed to connect to the window manager");
+            .map_err(|e| Error::Connection(format!("Failed to connect to the window manager: {}", e)))?;
 
         let wm_focused = active().id;
 
+        // Desktops and frames are read from the X server directly
+        let x11 = match Ewmh::connect() {
+            Ok(x11) => Some(x11),
+            Err(e) => {
+                logging::warning!("Failed to get desktops and frames, taking windows as they are: {}", e);
+                None
+            }
+        };
+        let current_desktop = x11.as_ref().and_then(|e| e.current());
+
         let mut visibility = Vec::with_capacity(wm_windows.len());
+        let mut on_desktop = Vec::with_capacity(wm_windows.len());
+        let mut groups = Vec::with_capacity(wm_windows.len());
         let windows = wm_windows.iter()
-            .inspect(|w| {
-                visibility.push(is_visible(&w.state()
-                    .expect("Failed to get window state")));
-            })
             .map(|w| {
                 let wm_win_geometry = w.geometry()
-                    .expect("Failed to get window geometry");
+                    .map_err(|e| Error::Protocol(format!("Failed to get window geometry: {}", e)))?;
                 let wm_win_states = w.state()
-                    .expect("Failed to get window state");
+                    .map_err(|e| Error::Protocol(format!("Failed to get window state: {}", e)))?;
+                // Windows of other desktops may be neither hidden nor withdrawn, only moved away
+                let desktop = x11.as_ref().and_then(|e| e.of_window(w.id));
+                let shown = ewmh::is_on_desktop(desktop, current_desktop);
+                visibility.push(is_visible(&wm_win_states) && shown);
+                on_desktop.push(shown);
+                // Window managers tabbing windows by their application group them by the leader
+                groups.push(x11.as_ref().and_then(|e| e.client_leader(w.id)));
                 let focused = w.id == wm_focused;
                 let floating = is_floating(&wm_win_states);
-                Window {
+                let rect = Rect {
+                    x: wm_win_geometry.0,
+                    y: wm_win_geometry.1,
+                    w: wm_win_geometry.2 as i32,
+                    h: wm_win_geometry.3 as i32,
+                };
+                // Navigation goes by what is seen, the decorated frame without client side shadows
+                let rect = match &x11 {
+                    Some(e) => ewmh::outer_frame(rect, e.extents(w.id, e.frame_extents), e.extents(w.id, e.gtk_frame_extents)),
+                    None => rect,
+                };
+                Ok(Window {
                     id: w.id as u64,
-                    rect: Rect {
-                        x: wm_win_geometry.0,
-                        y: wm_win_geometry.1,
-                        w: wm_win_geometry.2 as i32,
-                        h: wm_win_geometry.3 as i32,
+                    rect,
+                    focused,
+                    floating,
+                    urgent: is_urgent(&wm_win_states),
+                    fullscreen: is_fullscreen(&wm_win_states),
+                    properties: Properties {
+                        class: w.class().ok(),
+                        title: w.name().ok(),
+                        // Kinds are spelled like DropDownMenu, criteria ignore case and underscores
+                        window_type: w.kind().ok().map(|kind| format!("{:?}", kind).to_lowercase()),
+                        ..Default::default()
                     },
-                    focused: focused,
-                    floating: floating,
-                }
+                })
             })
-            .collect::<Windows>();
+            .collect::<Result<Windows>>()?;
 
-        Self {
-            windows: windows,
-            visibility: visibility,
-        }
+        let mapping = (0..windows.len()).collect();
+        Ok(Self {
+            windows,
+            visibility,
+            on_desktop,
+            groups,
+            mapping,
+        })
     }
 }
 
 impl GetTabs for Backend {
-    fn get_tabs(&self) -> Result<Windows, String> {
-        Err("Tabs not supported in this backend".to_string())
+    fn get_tabs(&self) -> Result<Windows> {
+        // Window managers tabbing windows don't tell about it, tabs are guessed
+        Ok(ewmh::emulated_tabs(&self.windows, &self.groups, &self.mapping, &self.on_desktop, &self.visibility))
     }
 }
 
 impl GetVisible for Backend {
-    fn get_visible(&self) -> Result<Windows, String> {
+    fn get_visible(&self) -> Result<Windows> {
         Ok(self.windows.iter()
             .enumerate()
             .filter_map(|(i, window)| {
@@ -75,19 +117,91 @@ impl GetVisible for Backend {
     }
 }
 
+impl GetGroups for Backend {
+    fn get_groups(&self) -> Result<Windows> {
+        // There are no containers, every window is a group of its own
+        self.get_visible()
+    }
+}
+
+impl GetScratchpad for Backend {
+    fn get_scratchpad(&self) -> Result<Windows> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+}
+
+impl GetContainers for Backend {
+    fn get_parent(&self) -> Result<Option<u64>> {
+        Err(Error::Unsupported("Containers not supported in this backend".to_string()))
+    }
+
+    fn get_child(&self) -> Result<Option<u64>> {
+        Err(Error::Unsupported("Containers not supported in this backend".to_string()))
+    }
+}
+
+impl GetOutputs for Backend {
+    fn get_outputs(&self) -> Result<Outputs> {
+        Err(Error::Unsupported("Outputs not supported in this backend".to_string()))
+    }
+}
+
+impl GetWorkspaces for Backend {
+    fn get_workspaces(&self) -> Result<Workspaces> {
+        // Desktops are the workspaces of EWMH
+        let desktops = Ewmh::connect()?;
+        match (desktops.count(), desktops.current()) {
+            (Some(count), Some(current)) => Ok(ewmh::desktop_workspaces(count, current, &desktops.names())),
+            _ => Err(Error::Unsupported("Window manager has no desktops".to_string())),
+        }
+    }
+}
+
+impl GetAll for Backend {
+    fn get_all(&self) -> Result<Windows> {
+        Ok(self.windows.clone())
+    }
+}
+
+impl MoveWindow for Backend {
+    fn swap_windows(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Moving windows not supported in this backend".to_string()))
+    }
+
+    fn move_window(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Moving windows not supported in this backend".to_string()))
+    }
+}
+
+impl ShowScratchpad for Backend {
+    fn show_scratchpad(&mut self, _window_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+
+    fn hide_scratchpad(&mut self, _window_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+}
+
+impl LeaveFullscreen for Backend {
+    fn leave_fullscreen(&mut self, _window_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Leaving fullscreen not supported in this backend".to_string()))
+    }
+}
+
 impl SetFocus for Backend {
-    fn set_focus(&mut self, window_id: &u64) {
+    fn set_focus(&mut self, window_id: &u64) -> Result<()> {
         // Connect to the X server
         let (conn, screen_num) = RustConnection::connect(None)
-            .expect("Failed to connect to the X server");
+            .map_err(|e| Error::Connection(format!("Failed to connect to the X server: {}", e)))?;
         let screen = &conn.setup().roots[screen_num];
 
         // Get the atom for _NET_ACTIVE_WINDOW
         let atom_name = b"_NET_ACTIVE_WINDOW";
         let net_active_window = conn.intern_atom(false, atom_name)
-            .expect("Failed to intern atom")
+            .map_err(|e| Error::Connection(format!("Failed to intern atom: {}", e)))?
             .reply()
-            .expect("Failed to get atom reply")
+            .map_err(|e| Error::Protocol(format!("Failed to get atom reply: {}", e)))?
             .atom;
 
         // Get the atom for _NET_WM_WINDOW_TYPE_NORMAL if needed (not strictly necessary for focusing)
@@ -114,20 +228,152 @@ impl SetFocus for Backend {
             screen.root,
             EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
             event,
-        ).expect("Failed to send event");
+        ).map_err(|e| Error::Connection(format!("Failed to send event: {}", e)))?;
         conn.flush()
-            .expect("Failed to flush connection");
+            .map_err(|e| Error::Connection(format!("Failed to flush connection: {}", e)))?;
+        Ok(())
     }
 }
 
-fn is_tiled(states: &Vec<State>) -> bool {
+fn is_tiled(states: &[State]) -> bool {
     states.iter().any(|state| matches!(state, State::MaxHorz | State::MaxVert))
 }
 
-fn is_floating(states: &Vec<State>) -> bool {
+fn is_floating(states: &[State]) -> bool {
     !is_tiled(states)
 }
 
-fn is_visible(states: &Vec<State>) -> bool {
+fn is_visible(states: &[State]) -> bool {
     !states.iter().any(|state| matches!(state, State::Hidden))
 }
+
+fn is_urgent(states: &[State]) -> bool {
+    states.iter().any(|state| matches!(state, State::DemandsAttention))
+}
+
+fn is_fullscreen(states: &[State]) -> bool {
+    states.iter().any(|state| matches!(state, State::Fullscreen))
+}
+
+impl FocusWorkspace for Backend {
+    fn focus_workspace(&mut self, workspace_id: &u64) -> Result<()> {
+        let desktop = u32::try_from(*workspace_id)
+            .map_err(|_| Error::Missing(format!("No desktop with ID: {}", workspace_id)))?;
+        logging::info!("Switching to desktop with ID: {}", workspace_id);
+        Ewmh::connect()?.switch(desktop)
+    }
+}
+
+/// What libwmctl doesn't tell about the windows and the window manager, like desktops and
+/// frame extents.
+struct Ewmh {
+    conn: RustConnection,
+    root: u32,
+    current_desktop: u32,
+    number_of_desktops: u32,
+    desktop_names: u32,
+    wm_desktop: u32,
+    frame_extents: u32,
+    gtk_frame_extents: u32,
+    client_leader: u32,
+    utf8_string: u32,
+}
+
+impl Ewmh {
+    fn connect() -> Result<Self> {
+        let (conn, screen_num) = RustConnection::connect(None)
+            .map_err(|e| Error::Connection(format!("Failed to connect to the X server: {}", e)))?;
+        let root = conn.setup().roots[screen_num].root;
+        let atom = |name: &[u8]| -> Result<u32> {
+            conn.intern_atom(false, name)
+                .map_err(|e| Error::Connection(format!("Failed to intern atom: {}", e)))?
+                .reply()
+                .map_err(|e| Error::Protocol(format!("Failed to get atom reply: {}", e)))
+                .map(|reply| reply.atom)
+        };
+        let current_desktop = atom(b"_NET_CURRENT_DESKTOP")?;
+        let number_of_desktops = atom(b"_NET_NUMBER_OF_DESKTOPS")?;
+        let desktop_names = atom(b"_NET_DESKTOP_NAMES")?;
+        let wm_desktop = atom(b"_NET_WM_DESKTOP")?;
+        let frame_extents = atom(b"_NET_FRAME_EXTENTS")?;
+        let gtk_frame_extents = atom(b"_GTK_FRAME_EXTENTS")?;
+        let client_leader = atom(b"WM_CLIENT_LEADER")?;
+        let utf8_string = atom(b"UTF8_STRING")?;
+        Ok(Self {
+            conn, root, current_desktop, number_of_desktops, desktop_names, wm_desktop, frame_extents,
+            gtk_frame_extents, client_leader, utf8_string,
+        })
+    }
+
+    /// Gets the desktop shown, `None` when the window manager has no desktops.
+    fn current(&self) -> Option<u32> {
+        self.cardinal(self.root, self.current_desktop)
+    }
+
+    fn count(&self) -> Option<u32> {
+        self.cardinal(self.root, self.number_of_desktops)
+    }
+
+    fn of_window(&self, window: u32) -> Option<u32> {
+        self.cardinal(window, self.wm_desktop)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.conn.get_property(false, self.root, self.desktop_names, self.utf8_string, 0, 1024).ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| ewmh::parse_desktop_names(&reply.value))
+            .unwrap_or_default()
+    }
+
+    fn extents(&self, window: u32, property: u32) -> ewmh::Extents {
+        self.conn.get_property(false, window, property, AtomEnum::CARDINAL, 0, 4).ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|values| values.collect::<Vec<u32>>()))
+            .map(|values| ewmh::Extents::parse(&values))
+            .unwrap_or_default()
+    }
+
+    fn client_leader(&self, window: u32) -> Option<u64> {
+        self.conn.get_property(false, window, self.client_leader, AtomEnum::WINDOW, 0, 1).ok()?
+            .reply().ok()?
+            .value32()?
+            .next()
+            .filter(|leader| *leader != 0)
+            .map(u64::from)
+    }
+
+    fn cardinal(&self, window: u32, property: u32) -> Option<u32> {
+        self.conn.get_property(false, window, property, AtomEnum::CARDINAL, 0, 1).ok()?
+            .reply().ok()?
+            .value32()?
+            .next()
+    }
+
+    /// Asks the window manager to switch the desktop, as a pager would.
+    fn switch(&self, desktop: u32) -> Result<()> {
+        let event = ClientMessageEvent {
+            response_type: 33, // CLIENT_MESSAGE
+            format: 32,
+            sequence: 0,
+            window: self.root,
+            type_: self.current_desktop,
+            data: x11rb::protocol::xproto::ClientMessageData::from([
+                desktop,
+                x11rb::CURRENT_TIME,
+                0,
+                0,
+                0,
+            ]),
+        };
+
+        self.conn.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        ).map_err(|e| Error::Connection(format!("Failed to send event: {}", e)))?;
+        self.conn.flush()
+            .map_err(|e| Error::Connection(format!("Failed to flush connection: {}", e)))?;
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use super::client::Client;
-use crate::types::Windows;
+use crate::backend::ewmh;
+use crate::types::{Outputs, Rect, Windows, Workspaces};
 use crate::backend::traits::*;
+use crate::error::{Error, Result};
 use xcb::Xid;
 use xcb::x::Window as XWindow;
 use std::collections::HashMap;
@@ -9,31 +11,51 @@ use crate::logging;
 pub struct Backend {
     client: Client,
     windows: Windows,
+    visibility: Vec<bool>,
+    on_desktop: Vec<bool>,
+    groups: Vec<Option<u64>>,
+    /// Index of the windows in `_NET_CLIENT_LIST`.
+    mapping: Vec<usize>,
     xid_map: HashMap<u64, XWindow>,
 }
 
 impl Backend {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         // Initialize the X client
-        let client = Client::new();
+        let client = Client::new()?;
 
         // Verify required atoms
-        client.verify_required_atoms()
-            .expect("Window manager does not support required atoms");
+        client.verify_required_atoms()?;
 
-        // Get the active window
-        let active_window = client.get_active_window()
-            .expect("Failed to get active window");
+        // Get the active window, there is none on an empty desktop
+        let active_window = client.get_active_window().ok();
 
-        // Get the list of windows
-        let xwindows = client.get_client_list();
+        // Windows of other desktops may be neither hidden nor withdrawn, only moved away
+        let current_desktop = client.get_current_desktop();
+
+        // Get the list of windows, topmost first where the stacking order is known, so that
+        // the topmost of equally good windows wins in navigation. The client list keeps the
+        // order windows were mapped in, which tabs go by.
+        let client_list = client.get_client_list()?;
+        let xwindows = match client.get_client_list_stacking() {
+            Ok(stacking) if !stacking.is_empty() => stacking.into_iter().rev().collect(),
+            Ok(_) => client_list.clone(),
+            Err(e) => {
+                logging::warning!("Falling back to unordered client list: {}", e);
+                client_list.clone()
+            }
+        };
 
         // Get the full window properties for each window
         let mut xid_map: HashMap<u64, XWindow> = HashMap::new();
+        let mut visibility = Vec::with_capacity(xwindows.len());
+        let mut on_desktop = Vec::with_capacity(xwindows.len());
+        let mut groups = Vec::with_capacity(xwindows.len());
+        let mut mapping = Vec::with_capacity(xwindows.len());
         let mut windows = xwindows.into_iter()
             .filter_map(|xwindow| {
                 // Fetch window info and add to the windows vector
-                let window = match client.fetch_window_info(&xwindow) {
+                let info = match client.fetch_window_info(&xwindow) {
                     Ok(info) => info,
                     Err(e) => {
                         logging::error!("Failed to fetch window info for {}: {}", xwindow.resource_id(), e);
@@ -41,43 +63,205 @@ impl Backend {
                     }
                 };
                 xid_map.insert(xwindow.resource_id().into(), xwindow);
-                Some(window)
+                let shown = ewmh::is_on_desktop(info.desktop, current_desktop);
+                visibility.push(!info.hidden && shown);
+                on_desktop.push(shown);
+                groups.push(info.group);
+                mapping.push(client_list.iter().position(|w| *w == xwindow).unwrap_or(usize::MAX));
+                Some(info.window)
             })
             .collect::<Windows>();
+        float_overlapping(&mut windows, &visibility);
 
         // Find the focused window
         windows.iter_mut().for_each(|window| {
-            if xid_map[&window.id] == active_window {
+            if Some(xid_map[&window.id]) == active_window {
                 window.focused = true;
             }
             logging::debug!("Window ID: {}, Rect: {}, Floating: {}, Focused: {}",
                      window.id, window.rect.to_string(), window.floating, window.focused);
         });
 
-        Backend { client, windows, xid_map }
+        Ok(Backend { client, windows, visibility, on_desktop, groups, mapping, xid_map })
+    }
+}
+
+/// Tiles never overlap, so of two visible windows overlapping, the smaller one floats, whether
+/// it is above or was lowered below the other. Of windows as big, the upper one floats, while
+/// windows framed by the same rect are tabs rather than floating.
+/// Windows are expected from the top of the stack, fullscreen windows cover everything anyway.
+fn float_overlapping(windows: &mut Windows, visibility: &[bool]) {
+    let candidates: Vec<usize> = (0..windows.len())
+        .filter(|index| visibility[*index] && !windows[*index].fullscreen)
+        .collect();
+    for (position, &upper) in candidates.iter().enumerate() {
+        for &lower in &candidates[position + 1..] {
+            let (above, below) = (windows[upper].rect, windows[lower].rect);
+            if above == below || !ewmh::overlap(&above, &below) {
+                continue;
+            }
+            let floating = if area(&below) < area(&above) { lower } else { upper };
+            windows[floating].floating = true;
+        }
     }
 }
 
+fn area(rect: &Rect) -> i64 {
+    rect.w as i64 * rect.h as i64
+}
+
 impl GetVisible for Backend {
-    fn get_visible(&self) -> Result<Windows, String> {
+    fn get_visible(&self) -> Result<Windows> {
+        Ok(self.windows.iter()
+            .zip(self.visibility.iter())
+            .filter(|(_, visible)| **visible)
+            .map(|(window, _)| window.clone())
+            .collect())
+    }
+}
+
+impl GetGroups for Backend {
+    fn get_groups(&self) -> Result<Windows> {
+        // There are no containers, every window is a group of its own
+        self.get_visible()
+    }
+}
+
+impl GetAll for Backend {
+    fn get_all(&self) -> Result<Windows> {
         Ok(self.windows.clone())
     }
 }
 
+impl GetScratchpad for Backend {
+    fn get_scratchpad(&self) -> Result<Windows> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+}
+
+impl GetContainers for Backend {
+    fn get_parent(&self) -> Result<Option<u64>> {
+        Err(Error::Unsupported("Containers not supported in this backend".to_string()))
+    }
+
+    fn get_child(&self) -> Result<Option<u64>> {
+        Err(Error::Unsupported("Containers not supported in this backend".to_string()))
+    }
+}
+
+impl GetOutputs for Backend {
+    fn get_outputs(&self) -> Result<Outputs> {
+        Err(Error::Unsupported("Outputs not supported in this backend".to_string()))
+    }
+}
+
+impl GetWorkspaces for Backend {
+    fn get_workspaces(&self) -> Result<Workspaces> {
+        // Desktops are the workspaces of EWMH
+        match (self.client.get_number_of_desktops(), self.client.get_current_desktop()) {
+            (Some(count), Some(current)) =>
+                Ok(ewmh::desktop_workspaces(count, current, &self.client.get_desktop_names())),
+            _ => Err(Error::Unsupported("Window manager has no desktops".to_string())),
+        }
+    }
+}
+
 impl GetTabs for Backend {
-    fn get_tabs(&self) -> Result<Windows, String> {
-        Err("Not implemented".to_string())
+    fn get_tabs(&self) -> Result<Windows> {
+        // Window managers tabbing windows don't tell about it, tabs are guessed
+        Ok(ewmh::emulated_tabs(&self.windows, &self.groups, &self.mapping, &self.on_desktop, &self.visibility))
+    }
+}
+
+impl MoveWindow for Backend {
+    fn swap_windows(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Moving windows not supported in this backend".to_string()))
+    }
+
+    fn move_window(&mut self, _window_id: &u64, _target_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Moving windows not supported in this backend".to_string()))
     }
 }
 
 impl SetFocus for Backend {
-    fn set_focus(&mut self, window_id: &u64) {
-        // Check if the window ID exists in the map
-        if !self.xid_map.contains_key(window_id) {
-            logging::critical!("Window ID {} does not exist", window_id);
-        }
+    fn set_focus(&mut self, window_id: &u64) -> Result<()> {
+        let xwindow = self.xid_map.get(window_id)
+            .ok_or(Error::Missing(format!("Window ID {} does not exist", window_id)))?;
         // Set focus to the specified window
-        self.client.set_focus(self.xid_map[window_id])
-            .expect("Failed to set focus");
+        self.client.set_focus(*xwindow)
+    }
+}
+
+impl ShowScratchpad for Backend {
+    fn show_scratchpad(&mut self, _window_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+
+    fn hide_scratchpad(&mut self, _window_id: &u64) -> Result<()> {
+        Err(Error::Unsupported("Scratchpad not supported in this backend".to_string()))
+    }
+}
+
+impl LeaveFullscreen for Backend {
+    fn leave_fullscreen(&mut self, window_id: &u64) -> Result<()> {
+        let xwindow = self.xid_map.get(window_id)
+            .ok_or(Error::Missing(format!("Window ID {} does not exist", window_id)))?;
+        logging::info!("Leaving fullscreen of window with ID: {}", window_id);
+        self.client.leave_fullscreen(*xwindow)?;
+        // The windows behind it were never hidden by their state, only by being covered
+        self.windows.iter_mut()
+            .filter(|window| window.id == *window_id)
+            .for_each(|window| window.fullscreen = false);
+        Ok(())
+    }
+}
+
+impl FocusWorkspace for Backend {
+    fn focus_workspace(&mut self, workspace_id: &u64) -> Result<()> {
+        let desktop = u32::try_from(*workspace_id)
+            .map_err(|_| Error::Missing(format!("No desktop with ID: {}", workspace_id)))?;
+        logging::info!("Switching to desktop with ID: {}", workspace_id);
+        self.client.set_current_desktop(desktop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Window;
+
+    fn window(id: u64, x: i32, y: i32, w: i32, h: i32) -> Window {
+        Window { id, rect: Rect { x, y, w, h }, ..Default::default() }
+    }
+
+    #[test]
+    fn test_float_overlapping() {
+        // From the top: a window over the left tile, the hidden one over everything, two tiles
+        let mut windows = vec![
+            window(1, 100, 100, 200, 200),
+            window(2, 0, 0, 1920, 1080),
+            window(3, 0, 0, 960, 1080),
+            window(4, 960, 0, 960, 1080),
+        ];
+        float_overlapping(&mut windows, &[true, false, true, true]);
+        let floating: Vec<u64> = windows.iter().filter(|w| w.floating).map(|w| w.id).collect();
+        assert_eq!(floating, vec![1]);
+
+        // The same window lowered below the left tile, and two windows tabbed in the right one
+        let mut windows = vec![
+            window(3, 0, 0, 960, 1080),
+            window(1, 100, 100, 200, 200),
+            window(4, 960, 0, 960, 1080),
+            window(5, 960, 0, 960, 1080),
+        ];
+        float_overlapping(&mut windows, &[true, true, true, true]);
+        let floating: Vec<u64> = windows.iter().filter(|w| w.floating).map(|w| w.id).collect();
+        assert_eq!(floating, vec![1]);
+
+        // Of overlapping windows as big, the upper one
+        let mut windows = vec![window(1, 100, 0, 960, 1080), window(2, 0, 0, 960, 1080)];
+        float_overlapping(&mut windows, &[true, true]);
+        let floating: Vec<u64> = windows.iter().filter(|w| w.floating).map(|w| w.id).collect();
+        assert_eq!(floating, vec![1]);
     }
 }
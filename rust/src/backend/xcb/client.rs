@@ -1,5 +1,9 @@
 use xcb::{x, Connection};
 use xcb::Xid;
+use crate::backend::ewmh;
+use crate::error::{Error, Result};
+use crate::logging;
+use crate::types::Properties;
 use crate::types::Rect;
 use crate::types::Window;
 
@@ -8,20 +12,45 @@ use crate::types::Window;
 xcb::atoms_struct! {
     #[derive(Copy, Clone, Debug)]
     pub struct Atoms {
-        pub _net_active_window           => b"_NET_ACTIVE_WINDOW",
-        pub _net_client_list             => b"_NET_CLIENT_LIST",
-        pub _net_supported               => b"_NET_SUPPORTED",
-        pub _net_wm_state                => b"_NET_WM_STATE",
-        pub _net_wm_state_hidden         => b"_NET_WM_STATE_HIDDEN",
-        pub _net_wm_state_maximized_horz => b"_NET_WM_STATE_MAXIMIZED_HORZ",
-        pub _net_wm_state_maximized_vert => b"_NET_WM_STATE_MAXIMIZED_VERT",
-        pub wm_state                     => b"WM_STATE",
-        pub wm_state_withdrawn           => b"WM_STATE_WITHDRAWN",
-        pub wm_state_normal              => b"WM_STATE_NORMAL",
-        pub wm_state_iconic              => b"WM_STATE_ICONIC",
+        pub _net_active_window              => b"_NET_ACTIVE_WINDOW",
+        pub _net_client_list                => b"_NET_CLIENT_LIST",
+        pub _net_client_list_stacking       => b"_NET_CLIENT_LIST_STACKING",
+        pub _net_current_desktop            => b"_NET_CURRENT_DESKTOP",
+        pub _net_desktop_names              => b"_NET_DESKTOP_NAMES",
+        pub _net_frame_extents              => b"_NET_FRAME_EXTENTS",
+        pub _net_number_of_desktops         => b"_NET_NUMBER_OF_DESKTOPS",
+        pub _net_supported                  => b"_NET_SUPPORTED",
+        pub _net_wm_desktop                 => b"_NET_WM_DESKTOP",
+        pub _net_wm_name                    => b"_NET_WM_NAME",
+        pub _net_wm_state                   => b"_NET_WM_STATE",
+        pub _net_wm_state_hidden            => b"_NET_WM_STATE_HIDDEN",
+        pub _net_wm_state_above             => b"_NET_WM_STATE_ABOVE",
+        pub _net_wm_state_demands_attention => b"_NET_WM_STATE_DEMANDS_ATTENTION",
+        pub _net_wm_state_fullscreen        => b"_NET_WM_STATE_FULLSCREEN",
+        pub _net_wm_state_maximized_horz    => b"_NET_WM_STATE_MAXIMIZED_HORZ",
+        pub _net_wm_state_maximized_vert    => b"_NET_WM_STATE_MAXIMIZED_VERT",
+        pub _net_wm_window_type             => b"_NET_WM_WINDOW_TYPE",
+        pub _gtk_frame_extents              => b"_GTK_FRAME_EXTENTS",
+        pub wm_client_leader                => b"WM_CLIENT_LEADER",
+        pub wm_state                        => b"WM_STATE",
+        pub wm_state_withdrawn              => b"WM_STATE_WITHDRAWN",
+        pub wm_state_normal                 => b"WM_STATE_NORMAL",
+        pub wm_state_iconic                 => b"WM_STATE_ICONIC",
+        pub utf8_string                     => b"UTF8_STRING",
     }
 }
 
+/// What is known about a client window, beyond what navigation needs.
+pub struct WindowInfo {
+    pub window: Window,
+    /// Withdrawn or hidden by the window manager, like minimized windows.
+    pub hidden: bool,
+    /// Desktop the window is on, if the window manager has desktops.
+    pub desktop: Option<u32>,
+    /// Client leader or window group, shared by the windows of one application.
+    pub group: Option<u64>,
+}
+
 pub struct Client {
     conn: Connection,
     root: x::Window,
@@ -29,60 +58,63 @@ pub struct Client {
 }
 
 impl Client {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         // Connect to the X server
         let (conn, screen_num) = Connection::connect(None)
-            .expect("Failed to connect to X server");
+            .map_err(|e| Error::Connection(format!("Failed to connect to X server: {}", e)))?;
 
         // Get the default screen
         // Modern setups use a single screen and distribute windows using XRandR
         let screen = conn.get_setup().roots().nth(screen_num as usize)
-            .expect("Failed to get screen");
+            .ok_or(Error::Protocol(format!("Failed to get screen {}", screen_num)))?;
 
         // Get the root window of the screen
         let root = screen.root();
 
         // Get all required atoms
         let atoms = Atoms::intern_all(&conn)
-            .expect("Failed to intern required atoms");
+            .map_err(|e| Error::Protocol(format!("Failed to intern required atoms: {}", e)))?;
 
-        Client { conn, root, atoms }
+        Ok(Client { conn, root, atoms })
     }
 
-    pub fn verify_required_atoms(&self) -> Result<(), String> {
-        // Get the _NET_SUPPORTED property from the root window
-        let cookie = self.conn.send_request(&x::GetProperty {
-            delete: false,
-            window: self.root,
-            property: self.atoms._net_supported,
-            r#type: x::ATOM_ATOM,
-            long_offset: 0,
-            long_length: 1024, // Number of atoms to fetch
-        });
-        let supported_atoms = self.conn.wait_for_reply(cookie)
-            .expect("Failed to get _NET_SUPPORTED property");
-        let supported_atoms = supported_atoms.value::<x::Atom>().to_vec();
+    pub fn verify_required_atoms(&self) -> Result<()> {
+        let supported_atoms = self.get_supported_atoms()?;
 
         // Check if all required atoms are supported
         for atom in [
-            &self.atoms._net_active_window,
             &self.atoms._net_client_list,
             &self.atoms._net_wm_state,
             &self.atoms._net_wm_state_maximized_horz,
             &self.atoms._net_wm_state_maximized_vert,
             &self.atoms._net_wm_state_hidden,
         ] {
-            if !supported_atoms.contains(&atom) {
-                return Err(format!(
+            if !supported_atoms.contains(atom) {
+                return Err(Error::Unsupported(format!(
                     "Required atom '{}' is not supported",
                     self.get_atom_name(*atom)?
-                ));
+                )));
             }
         }
         Ok(())
     }
 
-    pub fn get_client_list(&self) -> Vec<x::Window> {
+    fn get_supported_atoms(&self) -> Result<Vec<x::Atom>> {
+        // Get the _NET_SUPPORTED property from the root window
+        let cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: self.root,
+            property: self.atoms._net_supported,
+            r#type: x::ATOM_ATOM,
+            long_offset: 0,
+            long_length: 1024, // Number of atoms to fetch
+        });
+        let supported_atoms = self.conn.wait_for_reply(cookie)
+            .map_err(|e| Error::Protocol(format!("Failed to get _NET_SUPPORTED property: {}", e)))?;
+        Ok(supported_atoms.value::<x::Atom>().to_vec())
+    }
+
+    pub fn get_client_list(&self) -> Result<Vec<x::Window>> {
         // Get the list of client windows from the root window
         let cookie = self.conn.send_request(&x::GetProperty {
             delete: false,
@@ -94,12 +126,29 @@ impl Client {
         });
 
         let reply = self.conn.wait_for_reply(cookie)
-            .expect("Failed to get _NET_CLIENT_LIST property");
+            .map_err(|e| Error::Protocol(format!("Failed to get _NET_CLIENT_LIST property: {}", e)))?;
+
+        Ok(reply.value::<x::Window>().to_vec())
+    }
+
+    /// Gets the client windows from the bottom to the top of the stack.
+    pub fn get_client_list_stacking(&self) -> Result<Vec<x::Window>> {
+        let cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: self.root,
+            property: self.atoms._net_client_list_stacking,
+            r#type: x::ATOM_WINDOW,
+            long_offset: 0,
+            long_length: 1024, // Number of windows to fetch
+        });
+
+        let reply = self.conn.wait_for_reply(cookie)
+            .map_err(|e| Error::Protocol(format!("Failed to get _NET_CLIENT_LIST_STACKING property: {}", e)))?;
 
-        reply.value::<x::Window>().to_vec()
+        Ok(reply.value::<x::Window>().to_vec())
     }
 
-    pub fn get_active_window(&self) -> Result<x::Window, String> {
+    pub fn get_active_window(&self) -> Result<x::Window> {
         // Get the active window from the root window
         let cookie = self.conn.send_request(&x::GetProperty {
             delete: false,
@@ -111,22 +160,51 @@ impl Client {
         });
 
         let x_windows = self.conn.wait_for_reply(cookie)
-            .expect("Failed to get _NET_ACTIVE_WINDOW property")
+            .map_err(|e| Error::Protocol(format!("Failed to get _NET_ACTIVE_WINDOW property: {}", e)))?
             .value::<x::Window>().to_vec();
         match x_windows.first() {
             Some(window) => Ok(*window),
-            None => Err("No active window found".to_string()),
+            None => Err(Error::Missing("No active window found".to_string())),
         }
     }
 
-    pub fn fetch_window_info(&self, window_id: &x::Window) -> Result<Window, String> {
+    /// Gets the desktop shown, `None` when the window manager has no desktops.
+    pub fn get_current_desktop(&self) -> Option<u32> {
+        self.get_root_cardinal(self.atoms._net_current_desktop)
+    }
+
+    /// Gets the number of desktops, `None` when the window manager has no desktops.
+    pub fn get_number_of_desktops(&self) -> Option<u32> {
+        self.get_root_cardinal(self.atoms._net_number_of_desktops)
+    }
+
+    /// Gets the names of the desktops, they may be fewer than the desktops or none at all.
+    pub fn get_desktop_names(&self) -> Vec<String> {
+        let cookie = self.request_text(self.root, self.atoms._net_desktop_names, self.atoms.utf8_string);
+        self.conn.wait_for_reply(cookie)
+            .map(|reply| ewmh::parse_desktop_names(reply.value::<u8>()))
+            .unwrap_or_default()
+    }
+
+    /// Fetches the window information along with its state the backend keeps for itself.
+    pub fn fetch_window_info(&self, window_id: &x::Window) -> Result<WindowInfo> {
         // Request all necessary information about the window
         // asynchronously to use xcb properly.
         let cookies = (
-            self.request_geometry(window_id.clone()),
-            self.request_normalized_offset(window_id.clone()),
-            self.request_wm_state(window_id.clone()),
-            self.request_ewmh_state(window_id.clone()),
+            self.request_geometry(*window_id),
+            self.request_normalized_offset(*window_id),
+            self.request_wm_state(*window_id),
+            self.request_ewmh_state(*window_id),
+            self.request_text(*window_id, x::ATOM_WM_CLASS, x::ATOM_STRING),
+            self.request_text(*window_id, self.atoms._net_wm_name, self.atoms.utf8_string),
+            self.request_text(*window_id, x::ATOM_WM_NAME, x::ATOM_ANY),
+            self.request_wm_hints(*window_id),
+            self.request_window_type(*window_id),
+            self.request_window(*window_id, x::ATOM_WM_TRANSIENT_FOR),
+            self.request_cardinal(*window_id, self.atoms._net_wm_desktop),
+            self.request_extents(*window_id, self.atoms._net_frame_extents),
+            self.request_extents(*window_id, self.atoms._gtk_frame_extents),
+            self.request_window(*window_id, self.atoms.wm_client_leader),
         );
 
         // Wait for all requests to complete
@@ -135,74 +213,252 @@ impl Client {
             self.conn.wait_for_reply(cookies.1),
             self.conn.wait_for_reply(cookies.2),
             self.conn.wait_for_reply(cookies.3),
+            self.conn.wait_for_reply(cookies.4),
+            self.conn.wait_for_reply(cookies.5),
+            self.conn.wait_for_reply(cookies.6),
+            self.conn.wait_for_reply(cookies.7),
+            self.conn.wait_for_reply(cookies.8),
+            self.conn.wait_for_reply(cookies.9),
+            self.conn.wait_for_reply(cookies.10),
+            self.conn.wait_for_reply(cookies.11),
+            self.conn.wait_for_reply(cookies.12),
+            self.conn.wait_for_reply(cookies.13),
         );
 
         // Get geometry of the window
         let mut rect = match replies.0 {
             Ok(reply) => Rect::from(&reply),
-            Err(err) => return Err(format!("Failed to get window geometry: {}", err)),
+            Err(err) => return Err(Error::Protocol(format!("Failed to get window geometry: {}", err))),
         };
 
         // Get the normalized offset of the window
         match replies.1 {
             Ok(reply) => translate_rect(&mut rect, &reply),
-            Err(err) => return Err(format!("Failed to translate coordinates: {}", err)),
+            Err(err) => return Err(Error::Protocol(format!("Failed to translate coordinates: {}", err))),
         };
 
+        // Navigation goes by what is seen, the decorated frame without client side shadows
+        let frame = replies.11.map(|r| ewmh::Extents::parse(r.value::<u32>())).unwrap_or_default();
+        let client_side = replies.12.map(|r| ewmh::Extents::parse(r.value::<u32>())).unwrap_or_default();
+        let rect = ewmh::outer_frame(rect, frame, client_side);
+
         // Match WM state
         let wm_state = match replies.2 {
             Ok(reply) => reply.value::<x::Atom>().to_vec(),
-            Err(err) => return Err(format!("Failed to get WM state: {}", err)),
+            Err(err) => return Err(Error::Protocol(format!("Failed to get WM state: {}", err))),
         };
 
         // Match EWMH state
         let ewmh_state = match replies.3 {
             Ok(reply) => reply.value::<x::Atom>().to_vec(),
-            Err(err) => return Err(format!("Failed to get EWMH state: {}", err)),
+            Err(err) => return Err(Error::Protocol(format!("Failed to get EWMH state: {}", err))),
         };
 
-        if self.is_hidden(&wm_state, &ewmh_state) {
-            return Err("Skipping invisible window".to_string());
-        }
+        let hidden = self.is_hidden(&wm_state, &ewmh_state);
+
+        // Urgency is either requested from the window manager, or hinted to it by ICCCM
+        let wm_hints = replies.7.map(|r| r.value::<u32>().to_vec()).unwrap_or_default();
+        let urgent = ewmh_state.contains(&self.atoms._net_wm_state_demands_attention)
+            || is_urgency_hinted(&wm_hints);
+        let fullscreen = ewmh_state.contains(&self.atoms._net_wm_state_fullscreen);
 
-        Ok(Window {
+        // Properties are optional, windows are still usable without them
+        let (instance, class) = match replies.4 {
+            Ok(reply) => parse_wm_class(reply.value::<u8>()),
+            Err(_) => (None, None),
+        };
+        let title = replies.5.ok().and_then(|r| parse_text(r.value::<u8>()))
+            .or_else(|| replies.6.ok().and_then(|r| parse_text(r.value::<u8>())));
+        // The first type is the preferred one, the rest are fallbacks for older window managers
+        let window_type = match replies.8.ok().and_then(|r| r.value::<x::Atom>().first().copied()) {
+            Some(atom) => parse_window_type(&self.get_atom_name(atom)?),
+            None => None,
+        };
+        // Dialogs and the like are transient for the window they belong to
+        let transient = replies.9.is_ok_and(|r| r.value::<x::Window>().first().is_some_and(|w| !w.is_none()));
+        let floating = is_floating(window_type.as_deref(),
+            ewmh_state.contains(&self.atoms._net_wm_state_above), transient);
+        let desktop = replies.10.ok().and_then(|r| r.value::<u32>().first().copied());
+        // Window managers tabbing windows by their application group them by either of them
+        let group = replies.13.ok().and_then(|r| r.value::<x::Window>().first().copied())
+            .filter(|leader| !leader.is_none())
+            .map(|leader| leader.resource_id().into())
+            .or_else(|| window_group_hinted(&wm_hints));
+
+        let window = Window {
             id: window_id.resource_id().into(),
-            rect: rect,
-            floating: self.is_floating(),
+            rect,
+            floating,
             focused: false, // Focus state will be set later
-        })
+            urgent,
+            fullscreen,
+            properties: Properties { class, instance, title, window_type, ..Default::default() },
+        };
+        Ok(WindowInfo { window, hidden, desktop, group })
+    }
+
+    /// Activates the window the way pagers do, so that the window manager raises it, switches to
+    /// its desktop and keeps track of the focus. Only window managers without _NET_ACTIVE_WINDOW
+    /// get the input focus set and the window raised behind their back.
+    pub fn set_focus(&self, window_id: x::Window) -> Result<()> {
+        if self.get_supported_atoms()?.contains(&self.atoms._net_active_window) {
+            self.activate(window_id)
+        } else {
+            logging::debug!("No _NET_ACTIVE_WINDOW support, setting input focus directly.");
+            self.focus_and_raise(window_id)
+        }
+    }
+
+    fn activate(&self, window_id: x::Window) -> Result<()> {
+        // Focus stealing prevention compares the timestamp with the user time of other windows
+        let time = self.get_server_time().unwrap_or_else(|e| {
+            logging::warning!("Failed to get X server time, activating without it: {}", e);
+            x::CURRENT_TIME
+        });
+        let active = self.get_active_window().map(|w| w.resource_id()).unwrap_or(x::WINDOW_NONE.resource_id());
+        let event = x::ClientMessageEvent::new(
+            window_id,
+            self.atoms._net_active_window,
+            x::ClientMessageData::Data32([SOURCE_PAGER, time, active, 0, 0]),
+        );
+        let cookie = self.conn.send_request_checked(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(self.root),
+            event_mask: x::EventMask::SUBSTRUCTURE_NOTIFY | x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event,
+        });
+
+        match self.conn.check_request(cookie) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Command(format!("Failed to activate window: {}", err))),
+        }
     }
 
-    pub fn set_focus(&self, window_id: x::Window) -> Result<(), String> {
-        // Set focus to the specified window
-        let cookie = self.conn.send_request_checked(&x::SetInputFocus {
+    fn focus_and_raise(&self, window_id: x::Window) -> Result<()> {
+        // Set focus to the specified window, and bring it to the top of the stack
+        let focus = self.conn.send_request_checked(&x::SetInputFocus {
             focus: window_id,
-            revert_to: x::InputFocus::None,
+            revert_to: x::InputFocus::PointerRoot,
             time: x::CURRENT_TIME,
         });
+        let raise = self.conn.send_request_checked(&x::ConfigureWindow {
+            window: window_id,
+            value_list: &[x::ConfigWindow::StackMode(x::StackMode::Above)],
+        });
+
+        self.conn.check_request(focus)
+            .map_err(|err| Error::Command(format!("Failed to set focus: {}", err)))?;
+        self.conn.check_request(raise)
+            .map_err(|err| Error::Command(format!("Failed to raise window: {}", err)))
+    }
+
+    /// Gets the current time of the X server, from the property change event of a throwaway
+    /// window, as ICCCM suggests for clients without a user event at hand.
+    fn get_server_time(&self) -> Result<x::Timestamp> {
+        let window: x::Window = self.conn.generate_id();
+        self.conn.send_request(&x::CreateWindow {
+            depth: x::COPY_FROM_PARENT as u8,
+            wid: window,
+            parent: self.root,
+            x: -1,
+            y: -1,
+            width: 1,
+            height: 1,
+            border_width: 0,
+            class: x::WindowClass::InputOnly,
+            visual: x::COPY_FROM_PARENT,
+            value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE)],
+        });
+        // Appending nothing changes nothing, but still tells the time
+        self.conn.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Append,
+            window,
+            property: x::ATOM_WM_NAME,
+            r#type: x::ATOM_STRING,
+            data: &[] as &[u8],
+        });
+        self.conn.flush()
+            .map_err(|e| Error::Connection(format!("Failed to flush connection: {}", e)))?;
+
+        let time = loop {
+            match self.conn.wait_for_event() {
+                Ok(xcb::Event::X(x::Event::PropertyNotify(event))) if event.window() == window => break Ok(event.time()),
+                Ok(_) => continue,
+                Err(e) => break Err(Error::Protocol(format!("Failed to wait for property change: {}", e))),
+            }
+        };
+        self.conn.send_request(&x::DestroyWindow { window });
+        time
+    }
+
+    pub fn set_current_desktop(&self, desktop: u32) -> Result<()> {
+        // Ask the window manager to switch the desktop, as a pager would
+        let event = x::ClientMessageEvent::new(
+            self.root,
+            self.atoms._net_current_desktop,
+            x::ClientMessageData::Data32([desktop, x::CURRENT_TIME, 0, 0, 0]),
+        );
+        let cookie = self.conn.send_request_checked(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(self.root),
+            event_mask: x::EventMask::SUBSTRUCTURE_NOTIFY | x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event,
+        });
+
+        match self.conn.check_request(cookie) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::Command(format!("Failed to switch desktop: {}", err))),
+        }
+    }
+
+    pub fn leave_fullscreen(&self, window_id: x::Window) -> Result<()> {
+        // Ask the window manager to remove the fullscreen state, as a regular application
+        let event = x::ClientMessageEvent::new(
+            window_id,
+            self.atoms._net_wm_state,
+            x::ClientMessageData::Data32([
+                NET_WM_STATE_REMOVE,
+                self.atoms._net_wm_state_fullscreen.resource_id(),
+                0,
+                SOURCE_APPLICATION,
+                0,
+            ]),
+        );
+        let cookie = self.conn.send_request_checked(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(self.root),
+            event_mask: x::EventMask::SUBSTRUCTURE_NOTIFY | x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event,
+        });
 
         match self.conn.check_request(cookie) {
             Ok(_) => Ok(()),
-            Err(err) => Err(format!("Failed to set focus: {}", err)),
+            Err(err) => Err(Error::Command(format!("Failed to leave fullscreen: {}", err))),
         }
     }
 
-    fn get_atom_name(&self, atom: x::Atom) -> Result<String, String> {
+    fn get_atom_name(&self, atom: x::Atom) -> Result<String> {
         // Get the name of an atom
         let cookie = self.conn.send_request(&x::GetAtomName {
-            atom: atom,
+            atom,
         });
         match self.conn.wait_for_reply(cookie) {
             Ok(reply) => Ok(reply.name().to_string()),
-            Err(err) => Err(format!("Failed to get atom name: {}", err)),
+            Err(err) => Err(Error::Protocol(format!("Failed to get atom name: {}", err))),
         }
     }
 
+    fn get_root_cardinal(&self, property: x::Atom) -> Option<u32> {
+        let cookie = self.request_cardinal(self.root, property);
+        self.conn.wait_for_reply(cookie).ok()
+            .and_then(|reply| reply.value::<u32>().first().copied())
+    }
+
     fn request_normalized_offset(&self, src_window: x::Window)
         -> x::TranslateCoordinatesCookie {
         // Request to translate coordinates from one window to another
         self.conn.send_request(&x::TranslateCoordinates {
-            src_window: src_window,
+            src_window,
             dst_window: self.root,
             src_x: 0,
             src_y: 0,
@@ -243,14 +499,87 @@ impl Client {
         })
     }
 
-    fn is_floating(&self) -> bool {
-        return false; // Placeholder for floating logic
+    fn request_text(&self, window_id: x::Window, property: x::Atom, r#type: x::Atom)
+        -> x::GetPropertyCookie {
+        // Request to get a text property of a window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property,
+            r#type,
+            long_offset: 0,
+            long_length: 1024, // Number of 32-bit units to fetch
+        })
+    }
+
+    fn request_wm_hints(&self, window_id: x::Window)
+        -> x::GetPropertyCookie {
+        // Request to get ICCCM hints of a window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property: x::ATOM_WM_HINTS,
+            r#type: x::ATOM_WM_HINTS,
+            long_offset: 0,
+            long_length: 9, // Size of the WM_HINTS structure
+        })
+    }
+
+    fn request_window(&self, window_id: x::Window, property: x::Atom)
+        -> x::GetPropertyCookie {
+        // Request to get a property of a window pointing to another window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property,
+            r#type: x::ATOM_WINDOW,
+            long_offset: 0,
+            long_length: 1, // Only one window
+        })
+    }
+
+    fn request_cardinal(&self, window_id: x::Window, property: x::Atom)
+        -> x::GetPropertyCookie {
+        // Request to get a single number property of a window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property,
+            r#type: x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 1, // Only one number
+        })
+    }
+
+    fn request_extents(&self, window_id: x::Window, property: x::Atom)
+        -> x::GetPropertyCookie {
+        // Request to get the left, right, top and bottom extents of a window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property,
+            r#type: x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 4, // One number for every side
+        })
+    }
+
+    fn request_window_type(&self, window_id: x::Window)
+        -> x::GetPropertyCookie {
+        // Request to get EWMH types of a window
+        self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window: window_id,
+            property: self.atoms._net_wm_window_type,
+            r#type: x::ATOM_ATOM,
+            long_offset: 0,
+            long_length: 16, // Number of types to fetch
+        })
     }
 
-    fn is_hidden(&self, wm_state: &Vec<x::Atom>, ewmh_state: &Vec<x::Atom>) -> bool {
-        wm_state.first().expect("WM_STATE is missing window state")
-            == &self.atoms.wm_state_withdrawn.into() ||
-            ewmh_state.contains(&self.atoms._net_wm_state_hidden.into())
+    fn is_hidden(&self, wm_state: &[x::Atom], ewmh_state: &[x::Atom]) -> bool {
+        wm_state.first() == Some(&self.atoms.wm_state_withdrawn) ||
+            ewmh_state.contains(&self.atoms._net_wm_state_hidden)
     }
 }
 
@@ -266,7 +595,107 @@ impl From<&x::GetGeometryReply> for Rect {
 }
 
 fn translate_rect(rect: &mut Rect, translation: &x::TranslateCoordinatesReply) {
-    // Translate the rectangle coordinates based on the translation reply
-    rect.x += translation.dst_x() as i32;
-    rect.y += translation.dst_y() as i32;
+    // Geometry is relative to the parent, which is the frame under reparenting window managers,
+    // while the translated origin of the window is already relative to the root
+    rect.x = translation.dst_x() as i32;
+    rect.y = translation.dst_y() as i32;
+}
+
+/// Action of a _NET_WM_STATE client message removing the state.
+const NET_WM_STATE_REMOVE: u32 = 0;
+/// Source indication of client messages sent by regular applications.
+const SOURCE_APPLICATION: u32 = 1;
+/// Source indication of client messages sent by pagers and other tools acting for the user.
+const SOURCE_PAGER: u32 = 2;
+
+/// Urgency bit of the flags, the first field of WM_HINTS.
+const URGENCY_HINT: u32 = 1 << 8;
+
+fn is_urgency_hinted(wm_hints: &[u32]) -> bool {
+    wm_hints.first().is_some_and(|flags| flags & URGENCY_HINT != 0)
+}
+
+/// Window group bit of the flags, the group leader is the last field of WM_HINTS.
+const WINDOW_GROUP_HINT: u32 = 1 << 6;
+
+fn window_group_hinted(wm_hints: &[u32]) -> Option<u64> {
+    match wm_hints {
+        [flags, .., group] if wm_hints.len() == 9 && flags & WINDOW_GROUP_HINT != 0 && *group != 0 => Some(*group as u64),
+        _ => None,
+    }
+}
+
+/// Parses the null separated instance and class names of the WM_CLASS property.
+fn parse_wm_class(value: &[u8]) -> (Option<String>, Option<String>) {
+    let mut names = value.split(|b| *b == 0).map(parse_text);
+    (names.next().flatten(), names.next().flatten())
+}
+
+/// Parses a text property, empty text is treated as missing.
+fn parse_text(value: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(value).trim_end_matches('\0').to_string();
+    if text.is_empty() { None } else { Some(text) }
+}
+
+/// Window types of windows that are never tiled, by the names parsed out of their atoms.
+const FLOATING_TYPES: [&str; 9] = [
+    "dialog", "utility", "toolbar", "splash", "menu", "dropdown_menu", "popup_menu", "tooltip",
+    "notification",
+];
+
+/// Tells whether a window floats by what it says about itself: its type, being kept above the
+/// others, or being transient for another window. Overlapping windows are told by the backend.
+fn is_floating(window_type: Option<&str>, above: bool, transient: bool) -> bool {
+    above || transient || window_type.is_some_and(|t| FLOATING_TYPES.contains(&t))
+}
+
+/// Parses the window type out of the name of its atom, like `dialog` of
+/// `_NET_WM_WINDOW_TYPE_DIALOG`.
+fn parse_window_type(atom_name: &str) -> Option<String> {
+    atom_name.strip_prefix("_NET_WM_WINDOW_TYPE_")
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wm_class() {
+        assert_eq!(parse_wm_class(b"navigator\0Firefox\0"),
+            (Some("navigator".to_string()), Some("Firefox".to_string())));
+        assert_eq!(parse_wm_class(b"xterm\0"), (Some("xterm".to_string()), None));
+        assert_eq!(parse_wm_class(b""), (None, None));
+        assert_eq!(parse_text(b"~/src - vim\0"), Some("~/src - vim".to_string()));
+    }
+
+    #[test]
+    fn test_parse_window_type() {
+        assert_eq!(parse_window_type("_NET_WM_WINDOW_TYPE_DROPDOWN_MENU"), Some("dropdown_menu".to_string()));
+        assert_eq!(parse_window_type("_KDE_NET_WM_WINDOW_TYPE_OVERRIDE"), None);
+    }
+
+    #[test]
+    fn test_is_floating() {
+        assert!(is_floating(Some("dialog"), false, false));
+        assert!(is_floating(Some("normal"), true, false));
+        assert!(is_floating(None, false, true));
+        assert!(!is_floating(Some("normal"), false, false));
+        assert!(!is_floating(None, false, false));
+    }
+
+    #[test]
+    fn test_window_group_hinted() {
+        assert_eq!(window_group_hinted(&[WINDOW_GROUP_HINT, 0, 0, 0, 0, 0, 0, 0, 42]), Some(42));
+        assert_eq!(window_group_hinted(&[0, 0, 0, 0, 0, 0, 0, 0, 42]), None);
+        assert_eq!(window_group_hinted(&[WINDOW_GROUP_HINT, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_is_urgency_hinted() {
+        assert!(is_urgency_hinted(&[URGENCY_HINT | 1, 1, 0]));
+        assert!(!is_urgency_hinted(&[1, 1, 0]));
+        assert!(!is_urgency_hinted(&[]));
+    }
 }
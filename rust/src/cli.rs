@@ -1,3 +1,9 @@
+use crate::config::Config;
+use crate::error::Error;
+use crate::history;
+use crate::logging;
+use crate::navigation;
+use crate::types::{Criteria, TabLevel};
 use crate::planar;
 use crate::linear;
 use std::slice::Iter;
@@ -11,9 +17,22 @@ macro_rules! die {
 
 pub struct Cli {
     pub backend: UseBackend,
+    pub action: String,
+    pub scope: String,
     pub command: String,
     pub number: Option<usize>,
-    pub wrap: bool,
+    pub query: Option<String>,
+    pub criteria: Criteria,
+    pub run: Vec<String>,
+    pub wrap: Option<planar::Wrap>,
+    pub group: bool,
+    pub navigation: navigation::Options,
+    pub log_level: Option<logging::Level>,
+    pub history_depth: usize,
+    pub format: String,
+    pub dry_run: bool,
+    pub report: Option<String>,
+    pub target: Option<Box<Cli>>,
 }
 
 // The help message will be built at runtime, because rust does not support
@@ -21,14 +40,15 @@ pub struct Cli {
 // to conditionally include backend options based on the features enabled.
 // This should not have a significant performance impact, as the help message is only
 // used when the user requests it, and it is built once at runtime.
-const HELP: &'static [&'static str] = &["
+const HELP: &[&str] = &["
 i3switch - A simple command-line utility to switch focus in i3 window manager
 
-Usage: i3switch (<OPTION>|[<BACKEND>] <COMMAND> [wrap])
+Usage: i3switch (<OPTION>|[<BACKEND>] [<FLAGS>] [<ACTION>|<SCOPE>] <COMMAND> [wrap] [group] [outer])
 
-Backends:\n",
+Backends:
+  -auto         Detect the backend of the running window manager (default)\n",
 #[cfg(feature = "i3")]
-"  -i3           Use i3 backend (default)\n",
+"  -i3           Use i3 backend\n",
 #[cfg(feature = "wmctl")]
 "  -wm           Use wmctl backend\n",
 #[cfg(feature = "xcb")]
@@ -42,27 +62,109 @@ Commands:
   left          Move focus left
   up            Move focus up
   number NUM    Switch focus to tab/window number NUM
+  find QUERY    Switch focus to window best matching QUERY by class, instance, title, app id
+                or mark, repeat to cycle through further matches
+  urgent        Switch focus to window urgent for the longest time, on any workspace, repeat
+                to cycle through further urgent windows
+  raise-or-run <CRITERIA> -- CMD...
+                Switch focus to window meeting CRITERIA, repeat to cycle through further
+                windows, or run CMD if there is none
+  parent        Focus the container enclosing the focused window or container
+  child         Focus the child of the focused container it was left by
+  back          Switch focus to previously focused window
+  mru-next      Move focus to next window in focus history
+  mru-prev      Move focus to previous window in focus history
+  query windows|tabs
+                Print visible windows or tabs, without focusing anything
+  query target COMMAND
+                Print the windows, relation and sequence COMMAND navigates, and the window or
+                workspace it would focus, without focusing it
+",
+#[cfg(feature = "i3")]
+"  daemon        Keep the i3 tree cached and serve other invocations
+",
+"
+Actions:
+  move          Move focused window next to the window in direction of the command
+  swap          Swap focused window with the window in direction of the command
+
+Scopes:
+  workspace     Switch workspaces with next, prev, number and directions instead of windows
+  desktop       Switch desktops of EWMH window managers with next, prev and number, same as
+                workspaces
+  scratchpad    Show scratchpad windows with next, prev and number, hiding the shown one
+                like switching tabs (i3 and sway only)
+
+Criteria:
+  --class NAME     Window class, ignoring case
+  --instance NAME  Window instance, ignoring case
+  --title TEXT     Text contained in the window title, ignoring case
+  --app-id NAME    Wayland application id, ignoring case
+  --mark MARK      Mark of the window
+
+Flags:
+  --history-depth NUM  Number of windows remembered in focus history (default 16)
+  --format FORMAT      Format of query output, json or table (default table)
+  --dry-run            Print the window or workspace the command leads to and why, without
+                       focusing it
+  --level NUM          Switch the tabs of the tabbed or stacked container NUM levels above the
+                       innermost one, when they are nested (default 0)
+  --log-level LEVEL    Lowest level of printed messages, debug, info, warning or error
+                       (default warning)
+  --exclude RULE       Never switch to windows matching RULE, in addition to configured ones
+  --include RULE       Switch to windows matching RULE even when they are excluded
+  --fullscreen POLICY  Where directions lead from a fullscreen window, stay (default), leave
+                       its fullscreen for the windows behind it, or outputs with fullscreen
+                       windows
+
+Rules:
+  KEY=VALUE[,KEY=VALUE...]  Windows meeting all of the criteria, keys are class, instance,
+                            title, app-id, mark, type (normal, dialog, notification...) and
+                            floating (true or false), e.g. --exclude title=Picture-in-Picture
 
 Arguments:
   [wrap]        Wrap around when reaching the edge of the workspace
+  [wrap=output] Wrap around directions within the current output
+  [wrap=global] Wrap around directions to the output on the other end (same as wrap)
+  [nowrap]      Don't wrap around, even when configured to
+  [group]       Treat tabbed and stacked containers as a single window in directions
+  [outer]       Switch the tabs of the outermost of nested tabbed or stacked containers
 
 Options:
   -h, --help    Print help (see a summary with '-h')
   -V, --version Print version
+
+Configuration:
+  Defaults of the backend, wrap per command, log level, history depth and navigation are read
+  from $XDG_CONFIG_HOME/i3switch/config.toml, options given on the command line take precedence.
+
+Exit status:
+  0             Done
+  1             Nothing to switch to
+  2             Invalid arguments
+  3             Connection to the window manager failed
+  4             Reply of the window manager not understood
+  5             Window manager failed to carry out the command
+  6             Not supported by the backend
+  7             Window or workspace doesn't exist anymore
+  8             Command to run could not be started
 "];
 
 
 impl Cli {
-    pub fn parse(args: Vec<String>) -> Self {
-        let mut command = String::new();
-        let mut number: Option<usize> = None;
-        let mut wrap = false;
-
-        let mut arg_index = 1;
+    pub fn parse(args: Vec<String>, config: &Config) -> Self {
+        Self::print_info(&args);
 
-        // Handle the boring help and version flags ahead
+        match Self::try_parse(args, config) {
+            Ok(cli) => cli,
+            Err(message) => die!(Error::Usage(message.clone()).exit_code(), "Error: {}. Use -h for help.", message),
+        }
+    }
 
-        match args.get(arg_index) {
+    /// Handles the boring help and version flags, which need neither the configuration nor the
+    /// other arguments, and terminates the process when given.
+    pub fn print_info(args: &[String]) {
+        match args.get(1) {
             Some(arg) if arg == "-h" || arg == "--help" => {
                 die!(0, "{}", Self::help());
             }
@@ -71,11 +173,39 @@ impl Cli {
             }
             _ => {}
         }
+    }
+
+    /// Parses the command arguments without terminating the process on invalid input, so that
+    /// arguments received by the daemon can't bring it down.
+    /// What is not given by the arguments is taken from the configuration.
+    pub fn try_parse(args: Vec<String>, config: &Config) -> Result<Self, String> {
+        let mut action = "focus".to_string();
+        let mut scope = "window".to_string();
+        let mut command = String::new();
+        let mut number: Option<usize> = None;
+        let mut query: Option<String> = None;
+        let mut criteria = Criteria::default();
+        let mut run: Vec<String> = Vec::new();
+        let mut wrap: Option<planar::Wrap> = None;
+        let mut wrap_variant = false;
+        let mut nowrap = false;
+        let mut group = false;
+        let mut history_depth = config.history_depth.unwrap_or(history::DEFAULT_DEPTH);
+        let mut format = "table".to_string();
+        let mut dry_run = false;
+        let mut log_level: Option<logging::Level> = None;
+        let mut level: Option<TabLevel> = None;
+        let mut navigation = config.navigation.clone();
+        let mut report: Option<String> = None;
+        let mut target: Option<Box<Cli>> = None;
+
+        let mut arg_index = 1;
 
         // Let's take the happy path first
 
         let backend_arg = args.get(arg_index).map(|s| s.as_str());
         let backend = match backend_arg {
+            Some("-auto") => Some(UseBackend::Auto),
             #[cfg(feature = "i3")]
             Some("-i3")  => Some(UseBackend::I3),
             #[cfg(feature = "wmctl")]
@@ -89,14 +219,145 @@ impl Cli {
             arg_index += 1;
         }
 
-        let valid_commands = ["left", "right", "up", "down", "next", "prev", "number"];
+        while let Some(flag) = args.get(arg_index).filter(|a| a.starts_with("--")) {
+            match flag.as_str() {
+                "--history-depth" => {
+                    history_depth = args.get(arg_index + 1)
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .filter(|depth| *depth > 1)
+                        .ok_or("Invalid value for '--history-depth', expected a number above 1")?;
+                    arg_index += 2;
+                }
+                "--format" => {
+                    format = args.get(arg_index + 1)
+                        .filter(|v| ["json", "table"].contains(&v.as_str()))
+                        .ok_or("Invalid value for '--format', expected 'json' or 'table'")?
+                        .clone();
+                    arg_index += 2;
+                }
+                "--dry-run" => {
+                    dry_run = true;
+                    arg_index += 1;
+                }
+                "--exclude" | "--include" => {
+                    let rule = args.get(arg_index + 1)
+                        .ok_or(format!("No rule provided for '{}'", flag))?;
+                    let criteria = Criteria::parse(rule)
+                        .map_err(|e| format!("Invalid rule '{}': {}", rule, e))?;
+                    if flag == "--exclude" {
+                        navigation.exclude.push(criteria);
+                    } else {
+                        navigation.include.push(criteria);
+                    }
+                    arg_index += 2;
+                }
+                "--level" => {
+                    level = Some(TabLevel::Inner(args.get(arg_index + 1)
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .ok_or("Invalid value for '--level', expected a number")?));
+                    arg_index += 2;
+                }
+                "--fullscreen" => {
+                    navigation.fullscreen = args.get(arg_index + 1)
+                        .and_then(|v| navigation::Fullscreen::from_name(v))
+                        .ok_or("Invalid value for '--fullscreen', expected 'stay', 'leave' or 'outputs'")?;
+                    arg_index += 2;
+                }
+                "--log-level" => {
+                    log_level = Some(args.get(arg_index + 1)
+                        .and_then(|v| logging::Level::from_name(v))
+                        .ok_or("Invalid value for '--log-level', expected 'debug', 'info', 'warning' or 'error'")?);
+                    arg_index += 2;
+                }
+                _ => return Err(format!("Unknown flag '{}'", flag)),
+            }
+        }
+
+        if ["move", "swap"].contains(&args.get(arg_index).map(|s| s.as_str()).unwrap_or("")) {
+            action = args[arg_index].clone();
+            arg_index += 1;
+        }
+
+        if ["workspace", "scratchpad"].contains(&args.get(arg_index).map(|s| s.as_str()).unwrap_or("")) {
+            scope = args[arg_index].clone();
+            arg_index += 1;
+        }
+
+        // Desktops are what EWMH calls workspaces, they have no position to switch by direction
+        let desktop = scope == "window" && args.get(arg_index).is_some_and(|a| a == "desktop");
+        if desktop {
+            scope = "workspace".to_string();
+            arg_index += 1;
+        }
+
+        let valid_commands = [
+            "left", "right", "up", "down", "next", "prev", "number", "find", "raise-or-run",
+            "urgent", "parent", "child",
+            "back", "mru-next", "mru-prev", "query",
+            #[cfg(feature = "i3")]
+            "daemon",
+        ];
         if valid_commands.contains(&args.get(arg_index).map(|s| s.as_str()).unwrap_or("")) {
             command = args.get(arg_index).unwrap_or(&String::new()).clone();
             arg_index += 1;
         }
 
-        if args.get(arg_index) == Some(&"wrap".to_string()) {
-            wrap = true;
+        if command == "find" {
+            query = args.get(arg_index).cloned();
+            arg_index += 1;
+        }
+
+        if command == "raise-or-run" {
+            while let Some(option) = args.get(arg_index).filter(|a| *a != "--") {
+                let field = match option.as_str() {
+                    "--class" => &mut criteria.class,
+                    "--instance" => &mut criteria.instance,
+                    "--title" => &mut criteria.title,
+                    "--app-id" => &mut criteria.app_id,
+                    "--mark" => &mut criteria.mark,
+                    _ => return Err(format!("Unknown criterion '{}'", option)),
+                };
+                let value = args.get(arg_index + 1)
+                    .ok_or(format!("No value provided for '{}'", option))?;
+                *field = Some(value.clone());
+                arg_index += 2;
+            }
+            if args.get(arg_index).is_some() {
+                // Everything after the separator belongs to the command to run
+                run = args[arg_index + 1..].to_vec();
+                arg_index = args.len();
+            }
+        }
+
+        if command == "query" {
+            report = args.get(arg_index).cloned();
+            arg_index += 1;
+            if report.as_deref() == Some("target") {
+                // Everything after it is the command to report on
+                let mut nested = vec![args[0].clone()];
+                nested.extend(args.iter().skip(arg_index).cloned());
+                target = Some(Box::new(Self::try_parse(nested, config)?));
+                arg_index = args.len();
+            }
+        }
+
+        // Modifiers can come in any order, but only once each
+        loop {
+            match args.get(arg_index).map(|s| s.as_str()) {
+                Some(arg) if arg.starts_with("wrap") && wrap.is_none() && !nowrap => {
+                    wrap = match arg {
+                        "wrap" | "wrap=global" => Some(planar::Wrap::Global),
+                        "wrap=output" => Some(planar::Wrap::Output),
+                        _ => return Err(format!("Unknown wrap variant '{}'", arg)),
+                    };
+                    // Only directions know about outputs, the rest wraps just one way
+                    wrap_variant = arg != "wrap";
+                }
+                Some("nowrap") if wrap.is_none() && !nowrap => nowrap = true,
+                Some("group") if !group => group = true,
+                Some("outer") if level.is_none() => level = Some(TabLevel::Outer),
+                _ => break,
+            }
             arg_index += 1;
         }
 
@@ -108,32 +369,129 @@ impl Cli {
         // Now we can check if there's any issues with what we have got so far
 
         if args.get(arg_index).is_some() {
-            die!(1, "Error: Unexpected argument '{}'. Use -h for help.", args[arg_index]);
+            return Err(format!("Unexpected argument '{}'", args[arg_index]));
         }
 
         if command.is_empty() {
-            die!(1, "Error: No command provided. Use -h for help.");
+            return Err("No command provided".to_string());
         }
 
         if command == "number" {
             if number.is_none() {
-                die!(1, "Error: No number provided for 'number' command. Use -h for help.");
-            } else if wrap {
-                die!(1, "Error: Wrap option is not applicable for 'number' command. Use -h for help.");
+                return Err("No number provided for 'number' command".to_string());
+            } else if wrap.is_some() || nowrap {
+                return Err("Wrap option is not applicable for 'number' command".to_string());
+            }
+        }
+
+        if scope == "workspace" {
+            if action != "focus" {
+                return Err(format!("Action '{}' is not applicable for workspaces", action));
+            } else if group {
+                return Err("Group option is not applicable for workspaces".to_string());
+            } else if !["left", "right", "up", "down", "next", "prev", "number"].contains(&command.as_str()) {
+                return Err(format!("Command '{}' is not applicable for workspaces", command));
             }
         }
 
+        if desktop && !["next", "prev", "number"].contains(&command.as_str()) {
+            return Err(format!("Command '{}' is not applicable for desktops", command));
+        }
+
+        if scope == "scratchpad" {
+            if action != "focus" {
+                return Err(format!("Action '{}' is not applicable for scratchpad", action));
+            } else if group {
+                return Err("Group option is not applicable for scratchpad".to_string());
+            } else if !["next", "prev", "number"].contains(&command.as_str()) {
+                return Err(format!("Command '{}' is not applicable for scratchpad", command));
+            }
+        }
+
+        if command == "find" {
+            if query.as_deref().is_none_or(|q| q.trim().is_empty()) {
+                return Err("No query provided for 'find' command".to_string());
+            } else if wrap.is_some() || nowrap {
+                return Err("Wrap option is not applicable for 'find' command, it always cycles".to_string());
+            }
+        }
+
+        if command == "raise-or-run" {
+            if criteria.is_empty() {
+                return Err("No criteria provided for 'raise-or-run' command".to_string());
+            } else if run.is_empty() {
+                return Err("No command to run provided after '--' for 'raise-or-run' command".to_string());
+            }
+        }
+
+        if command == "query" {
+            if !["windows", "tabs", "target"].contains(&report.as_deref().unwrap_or("")) {
+                return Err("Expected 'windows', 'tabs' or 'target' for 'query' command".to_string());
+            }
+            if let Some(nested) = target.as_ref().filter(|t| ["query", "daemon"].contains(&t.command.as_str())) {
+                return Err(format!("Command '{}' can't be queried", nested.command));
+            }
+        }
+
+        if dry_run && ["query", "daemon"].contains(&command.as_str()) {
+            return Err(format!("Dry run is not applicable for '{}' command", command));
+        }
+
+        if action != "focus" && !["left", "right", "up", "down"].contains(&command.as_str()) {
+            return Err(format!("Action '{}' requires a direction", action));
+        }
+
+        if group && !["left", "right", "up", "down"].contains(&command.as_str()) {
+            return Err(format!("Group option is not applicable for '{}' command", command));
+        }
+
+        if let Some(level) = level {
+            if scope != "window" || !["next", "prev", "number"].contains(&command.as_str()) {
+                return Err(format!("Tab level is only applicable for next, prev and number of windows, not '{}' command", command));
+            }
+            navigation.tabs = level;
+        }
+
+        if ["daemon", "back", "urgent", "query", "parent", "child"].contains(&command.as_str()) && (wrap.is_some() || nowrap) {
+            return Err(format!("Wrap option is not applicable for '{}' command", command));
+        }
+
+        if wrap_variant && !["left", "right", "up", "down"].contains(&command.as_str()) {
+            return Err(format!("Wrap variants are only applicable for directions, not '{}' command", command));
+        }
+
         // Any defaults we need to set
 
-        let backend = backend.unwrap_or(*UseBackend::iter().next()
-            .expect("At least one backend should be available"));
+        let backend = backend.or(config.backend).unwrap_or(UseBackend::Auto);
 
-        Cli {
+        if wrap.is_none() && !nowrap {
+            wrap = config.wrap(&scope, &command);
+        }
+
+        #[cfg(feature = "i3")]
+        if command == "daemon" && ![UseBackend::I3, UseBackend::Auto].contains(&backend) {
+            return Err("Daemon is only available for i3 backend".to_string());
+        }
+
+        Ok(Cli {
             backend,
+            action,
+            scope,
             command,
             number,
+            query,
+            criteria,
+            run,
             wrap,
-        }
+            group,
+            navigation,
+            log_level: log_level.or(config.log_level),
+            history_depth,
+            format,
+            dry_run,
+            report,
+            target,
+        })
     }
 
     pub fn help() -> String {
@@ -148,6 +506,14 @@ impl Cli {
         }
     }
 
+    pub fn history_direction(&self) -> Option<linear::Direction> {
+        match self.command.as_str() {
+            "mru-next" => Some(linear::Direction::Next),
+            "mru-prev" => Some(linear::Direction::Prev),
+            _ => None,
+        }
+    }
+
     pub fn planar_direction(&self) -> Option<planar::Direction> {
         match self.command.as_str() {
             "left"  => Some(planar::Direction::Left),
@@ -161,6 +527,8 @@ impl Cli {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UseBackend {
+    /// The first backend that works with the running window manager.
+    Auto,
     #[cfg(feature = "i3")]
     I3,
     #[cfg(feature = "wmctl")]
@@ -170,16 +538,18 @@ pub enum UseBackend {
 }
 
 impl UseBackend {
+    /// Returns the available backends in the order they are detected, the dedicated i3 IPC
+    /// first, then the EWMH ones.
     pub fn iter() -> Iter<'static, UseBackend> {
         static BACKENDS: &[UseBackend] = &[
             #[cfg(feature = "i3")]
             UseBackend::I3,
-            #[cfg(feature = "wmctl")]
-            UseBackend::WmCtl,
             #[cfg(feature = "xcb")]
             UseBackend::Xcb,
+            #[cfg(feature = "wmctl")]
+            UseBackend::WmCtl,
         ];
-        return BACKENDS.iter();
+        BACKENDS.iter()
     }
 }
 
@@ -191,42 +561,407 @@ mod tests {
     fn test_cli_parse() {
         let args = "i3switch -i3 next wrap"
             .to_string().split_whitespace().map(String::from).collect();
-        let cli = Cli::parse(args);
+        let cli = Cli::parse(args, &Config::default());
         assert_eq!(cli.backend, UseBackend::I3);
         assert_eq!(cli.command, "next");
-        assert!(cli.wrap);
+        assert!(cli.wrap.is_some());
         assert!(cli.number.is_none());
 
-        let args = "i3switch -wm prev"
-            .to_string().split_whitespace().map(String::from).collect();
-        let cli = Cli::parse(args);
-        assert_eq!(cli.backend, UseBackend::WmCtl);
-        assert_eq!(cli.command, "prev");
-        assert!(!cli.wrap);
-        assert!(cli.number.is_none());
+        #[cfg(feature = "wmctl")]
+        {
+            let args = "i3switch -wm prev"
+                .to_string().split_whitespace().map(String::from).collect();
+            let cli = Cli::parse(args, &Config::default());
+            assert_eq!(cli.backend, UseBackend::WmCtl);
+            assert_eq!(cli.command, "prev");
+            assert!(cli.wrap.is_none());
+            assert!(cli.number.is_none());
+        }
 
         let args = "i3switch -xcb number 3"
             .to_string().split_whitespace().map(String::from).collect();
-        let cli = Cli::parse(args);
+        let cli = Cli::parse(args, &Config::default());
         assert_eq!(cli.backend, UseBackend::Xcb);
         assert_eq!(cli.command, "number");
-        assert!(!cli.wrap);
+        assert!(cli.wrap.is_none());
         assert_eq!(cli.number, Some(3));
 
         let args = "i3switch -i3 up wrap"
             .to_string().split_whitespace().map(String::from).collect();
-        let cli = Cli::parse(args);
+        let cli = Cli::parse(args, &Config::default());
         assert_eq!(cli.backend, UseBackend::I3);
         assert_eq!(cli.command, "up");
-        assert!(cli.wrap);
+        assert!(cli.wrap.is_some());
         assert!(cli.number.is_none());
 
         let args = "i3switch left"
             .to_string().split_whitespace().map(String::from).collect();
-        let cli = Cli::parse(args);
-        assert_eq!(cli.backend, UseBackend::I3);
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.backend, UseBackend::Auto);
         assert_eq!(cli.command, "left");
-        assert!(!cli.wrap);
+        assert!(cli.wrap.is_none());
         assert!(cli.number.is_none());
     }
+
+    #[test]
+    fn test_cli_parse_history() {
+        let args = "i3switch --history-depth 5 mru-next wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "mru-next");
+        assert_eq!(cli.history_depth, 5);
+        assert!(cli.wrap.is_some());
+        assert_eq!(cli.history_direction(), Some(linear::Direction::Next));
+
+        let args = "i3switch back"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "back");
+        assert_eq!(cli.history_depth, history::DEFAULT_DEPTH);
+        assert_eq!(cli.history_direction(), None);
+
+        let config = Config::parse("history-depth = 8").unwrap();
+        let args = "i3switch mru-prev"
+            .to_string().split_whitespace().map(String::from).collect();
+        assert_eq!(Cli::parse(args, &config).history_depth, 8);
+        let args = "i3switch --history-depth 5 mru-prev"
+            .to_string().split_whitespace().map(String::from).collect();
+        assert_eq!(Cli::parse(args, &config).history_depth, 5);
+    }
+
+    #[test]
+    fn test_cli_parse_action() {
+        let args = "i3switch move left wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.action, "move");
+        assert_eq!(cli.planar_direction(), Some(planar::Direction::Left));
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch swap down"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.action, "swap");
+        assert_eq!(cli.planar_direction(), Some(planar::Direction::Down));
+
+        let args = "i3switch up"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.action, "focus");
+        assert!(!cli.group);
+    }
+
+    #[test]
+    fn test_cli_parse_group() {
+        let args = "i3switch right group wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "right");
+        assert!(cli.group);
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch left wrap group"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert!(cli.group);
+        assert!(cli.wrap.is_some());
+    }
+
+    #[test]
+    fn test_cli_parse_wrap() {
+        let args = "i3switch up wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.wrap, Some(planar::Wrap::Global));
+
+        let args = "i3switch left wrap=output"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.wrap, Some(planar::Wrap::Output));
+
+        let args = "i3switch group swap down wrap=global"
+            .to_string().split_whitespace().map(String::from).collect();
+        assert!(Cli::try_parse(args, &Config::default()).is_err());
+
+        let args = "i3switch swap down wrap=global group"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.wrap, Some(planar::Wrap::Global));
+        assert!(cli.group);
+    }
+
+    #[test]
+    fn test_cli_parse_workspace() {
+        let args = "i3switch workspace next wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "workspace");
+        assert_eq!(cli.command, "next");
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch workspace number 3"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "workspace");
+        assert_eq!(cli.number, Some(3));
+
+        let args = "i3switch left"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "window");
+    }
+
+    #[test]
+    fn test_cli_parse_desktop() {
+        let args = "i3switch desktop prev wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "workspace");
+        assert_eq!(cli.command, "prev");
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch desktop number 2"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "workspace");
+        assert_eq!(cli.number, Some(2));
+    }
+
+    #[test]
+    fn test_cli_parse_scratchpad() {
+        let args = "i3switch scratchpad prev wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "scratchpad");
+        assert_eq!(cli.command, "prev");
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch scratchpad number 1"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.scope, "scratchpad");
+        assert_eq!(cli.number, Some(1));
+    }
+
+    #[test]
+    fn test_cli_parse_tab_level() {
+        let args = "i3switch --level 1 next wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.navigation.tabs, TabLevel::Inner(1));
+        assert!(cli.wrap.is_some());
+
+        let args = "i3switch number outer 2"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.navigation.tabs, TabLevel::Outer);
+        assert_eq!(cli.number, Some(2));
+
+        let args = "i3switch parent"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "parent");
+        assert_eq!(cli.navigation.tabs, TabLevel::Inner(0));
+    }
+
+    #[test]
+    fn test_cli_parse_find() {
+        let args = vec!["i3switch".to_string(), "find".to_string(), "text editor".to_string()];
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "find");
+        assert_eq!(cli.query.as_deref(), Some("text editor"));
+
+        let args = "i3switch left"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.query, None);
+    }
+
+    #[test]
+    fn test_cli_parse_raise_or_run() {
+        let args = "i3switch raise-or-run --class Firefox --title docs -- firefox --new-window"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "raise-or-run");
+        assert_eq!(cli.criteria.class.as_deref(), Some("Firefox"));
+        assert_eq!(cli.criteria.title.as_deref(), Some("docs"));
+        assert_eq!(cli.criteria.instance, None);
+        assert_eq!(cli.run, vec!["firefox", "--new-window"]);
+
+        // Modifiers after the separator are arguments of the command
+        let args = "i3switch raise-or-run --mark term -- alacritty wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.criteria.mark.as_deref(), Some("term"));
+        assert_eq!(cli.run, vec!["alacritty", "wrap"]);
+        assert!(cli.wrap.is_none());
+    }
+
+    #[test]
+    fn test_cli_parse_query() {
+        let args = "i3switch --format json query tabs"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.command, "query");
+        assert_eq!(cli.report.as_deref(), Some("tabs"));
+        assert_eq!(cli.format, "json");
+        assert!(cli.target.is_none());
+
+        let args = "i3switch query target workspace left wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert_eq!(cli.format, "table");
+        assert!(cli.wrap.is_none());
+        let target = cli.target.expect("Query of target should have a target command");
+        assert_eq!(target.scope, "workspace");
+        assert_eq!(target.command, "left");
+        assert!(target.wrap.is_some());
+    }
+
+    #[test]
+    fn test_cli_parse_dry_run() {
+        let args = "i3switch --dry-run swap left"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert!(cli.dry_run);
+        assert_eq!(cli.action, "swap");
+
+        let args = "i3switch --format json --dry-run workspace next"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert!(cli.dry_run);
+        assert_eq!(cli.format, "json");
+
+        let args = "i3switch next"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &Config::default());
+        assert!(!cli.dry_run);
+    }
+
+    #[test]
+    fn test_cli_parse_config() {
+        let config = Config::parse("
+            backend = \"xcb\"
+            log-level = \"error\"
+            linear = \"tabs\"
+            fullscreen = \"leave\"
+            [wrap]
+            next = true
+            left = \"output\"
+        ").unwrap();
+
+        let args = "i3switch next"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &config);
+        assert_eq!(cli.backend, UseBackend::Xcb);
+        assert_eq!(cli.wrap, Some(planar::Wrap::Global));
+        assert_eq!(cli.log_level, Some(logging::Level::ERROR));
+        assert_eq!(cli.navigation.linear, navigation::Linear::Tabs);
+        assert!(cli.navigation.exclude.is_empty());
+        assert_eq!(cli.navigation.fullscreen, navigation::Fullscreen::Leave);
+
+        // Command line takes precedence
+        let args = "i3switch -i3 --log-level debug --fullscreen stay left nowrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &config);
+        assert_eq!(cli.backend, UseBackend::I3);
+        assert_eq!(cli.wrap, None);
+        assert_eq!(cli.log_level, Some(logging::Level::DEBUG));
+        assert_eq!(cli.navigation.fullscreen, navigation::Fullscreen::Stay);
+
+        let args = "i3switch left wrap"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &config);
+        assert_eq!(cli.wrap, Some(planar::Wrap::Global));
+
+        let args = "i3switch --exclude class=Conky --exclude type=dock,floating=true --include title=notes left"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &config);
+        assert_eq!(cli.navigation.exclude.len(), 2);
+        assert_eq!(cli.navigation.exclude[1].floating, Some(true));
+        assert_eq!(cli.navigation.include[0].title.as_deref(), Some("notes"));
+
+        // Configured wrap belongs to windows only
+        let args = "i3switch workspace next"
+            .to_string().split_whitespace().map(String::from).collect();
+        let cli = Cli::parse(args, &config);
+        assert_eq!(cli.wrap, None);
+    }
+
+    #[test]
+    fn test_cli_try_parse_errors() {
+        let parse = |line: &str| Cli::try_parse(line.split_whitespace().map(String::from).collect(), &Config::default());
+        assert!(parse("i3switch").is_err());
+        assert!(parse("i3switch sideways").is_err());
+        assert!(parse("i3switch next wrap extra").is_err());
+        assert!(parse("i3switch scratchpad left").is_err());
+        assert!(parse("i3switch swap scratchpad next").is_err());
+        assert!(parse("i3switch number").is_err());
+        assert!(parse("i3switch number wrap 3").is_err());
+        assert!(parse("i3switch daemon wrap").is_err());
+        assert!(parse("i3switch back wrap").is_err());
+        assert!(parse("i3switch next wrap=output").is_err());
+        assert!(parse("i3switch mru-prev wrap=global").is_err());
+        assert!(parse("i3switch left wrap=workspace").is_err());
+        assert!(parse("i3switch left wrap wrap=output").is_err());
+        assert!(parse("i3switch workspace back").is_err());
+        assert!(parse("i3switch workspace left group").is_err());
+        assert!(parse("i3switch desktop left").is_err());
+        assert!(parse("i3switch workspace desktop next").is_err());
+        assert!(parse("i3switch move desktop next").is_err());
+        assert!(parse("i3switch move workspace left").is_err());
+        assert!(parse("i3switch workspace").is_err());
+        assert!(parse("i3switch find").is_err());
+        assert!(parse("i3switch find term wrap").is_err());
+        assert!(parse("i3switch find term term").is_err());
+        assert!(parse("i3switch swap find term").is_err());
+        assert!(parse("i3switch workspace find term").is_err());
+        assert!(parse("i3switch raise-or-run -- alacritty").is_err());
+        assert!(parse("i3switch urgent wrap").is_err());
+        assert!(parse("i3switch --level next").is_err());
+        assert!(parse("i3switch --level one next").is_err());
+        assert!(parse("i3switch --level 1 next outer").is_err());
+        assert!(parse("i3switch left outer").is_err());
+        assert!(parse("i3switch workspace next outer").is_err());
+        assert!(parse("i3switch parent wrap").is_err());
+        assert!(parse("i3switch child outer").is_err());
+        assert!(parse("i3switch workspace urgent").is_err());
+        assert!(parse("i3switch move urgent").is_err());
+        assert!(parse("i3switch raise-or-run --class Alacritty").is_err());
+        assert!(parse("i3switch raise-or-run --class Alacritty --").is_err());
+        assert!(parse("i3switch raise-or-run --class").is_err());
+        assert!(parse("i3switch raise-or-run --name term -- alacritty").is_err());
+        assert!(parse("i3switch --history-depth back").is_err());
+        assert!(parse("i3switch --history-depth 1 back").is_err());
+        assert!(parse("i3switch --unknown back").is_err());
+        assert!(parse("i3switch move").is_err());
+        assert!(parse("i3switch swap next").is_err());
+        assert!(parse("i3switch move number 2").is_err());
+        assert!(parse("i3switch next group").is_err());
+        assert!(parse("i3switch left group group").is_err());
+        assert!(parse("i3switch query").is_err());
+        assert!(parse("i3switch query outputs").is_err());
+        assert!(parse("i3switch query windows wrap").is_err());
+        assert!(parse("i3switch query target").is_err());
+        assert!(parse("i3switch query target sideways").is_err());
+        assert!(parse("i3switch query target query tabs").is_err());
+        assert!(parse("i3switch move query tabs").is_err());
+        assert!(parse("i3switch --format yaml query tabs").is_err());
+        assert!(parse("i3switch --dry-run query tabs").is_err());
+        assert!(parse("i3switch next --dry-run").is_err());
+        assert!(parse("i3switch next wrap nowrap").is_err());
+        assert!(parse("i3switch left nowrap wrap=output").is_err());
+        assert!(parse("i3switch number nowrap 2").is_err());
+        assert!(parse("i3switch back nowrap").is_err());
+        assert!(parse("i3switch --log-level loud back").is_err());
+        assert!(parse("i3switch --exclude next").is_err());
+        assert!(parse("i3switch --include floating=maybe next").is_err());
+        #[cfg(feature = "i3")]
+        assert!(parse("i3switch -i3 daemon").is_ok());
+        #[cfg(feature = "i3")]
+        assert!(parse("i3switch daemon").is_ok());
+        assert!(parse("i3switch -dwm next").is_err());
+        #[cfg(all(feature = "i3", feature = "xcb"))]
+        assert!(parse("i3switch -xcb daemon").is_err());
+    }
 }
@@ -0,0 +1,399 @@
+//! Configuration file with the defaults of what is not given on the command line.
+//!
+//! The file is looked up as `i3switch/config.toml` in `$XDG_CONFIG_HOME` (`~/.config`), then in
+//! `$XDG_CONFIG_DIRS` (`/etc/xdg`). Only as much of TOML as the settings need is understood, that
+//! is `[tables]`, and `key = value` lines of single line strings without escapes, integers,
+//! booleans and arrays of strings on a single line. Inline tables, dotted keys, arrays of tables,
+//! multi-line values and escapes are refused with the line they are on:
+//!
+//! ```toml
+//! # Backend used when none is given: auto, i3, xcb or wm
+//! backend = "xcb"
+//! # Lowest level of printed messages: debug, info, warning or error
+//! log-level = "warning"
+//! # Number of windows remembered in focus history, above 1
+//! history-depth = 16
+//! # What next, prev and number go through while a floating window is focused: floating or tabs
+//! linear = "tabs"
+//! # Where directions lead from a fullscreen window: stay, leave or outputs
+//! fullscreen = "leave"
+//! # Windows never switched to, and exceptions to them, by criteria like those of --exclude
+//! exclude = ["class=Conky", "title=Picture-in-Picture,floating=true", "type=notification"]
+//! include = ["class=mpv"]
+//!
+//! # How windows relate in directions: border or center
+//! [relation]
+//! tiled = "border"
+//! floating = "center"
+//!
+//! # Wrap of commands given neither wrap nor nowrap: true, false, "global" or "output"
+//! [wrap]
+//! next = true
+//! left = "output"
+//!
+//! # The same for workspace commands
+//! [wrap.workspace]
+//! next = true
+//!
+//! # And for scratchpad commands
+//! [wrap.scratchpad]
+//! next = true
+//! ```
+
+use crate::cli::UseBackend;
+use crate::error::{Error, Result};
+use crate::logging;
+use crate::navigation;
+use crate::paths;
+use crate::planar;
+use crate::types::Criteria;
+
+use std::collections::HashMap;
+
+const DIRECTIONS: &[&str] = &["left", "right", "up", "down"];
+const WINDOW_WRAPS: &[&str] = &["left", "right", "up", "down", "next", "prev", "mru-next", "mru-prev"];
+const WORKSPACE_WRAPS: &[&str] = &["left", "right", "up", "down", "next", "prev"];
+const SCRATCHPAD_WRAPS: &[&str] = &["next", "prev"];
+
+#[derive(Debug, Default)]
+pub struct Config {
+    pub backend: Option<UseBackend>,
+    pub log_level: Option<logging::Level>,
+    pub history_depth: Option<usize>,
+    pub navigation: navigation::Options,
+    /// Wrap of the commands that wrap, by scope and command.
+    wrap: HashMap<String, planar::Wrap>,
+}
+
+/// Value of a setting.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+impl Config {
+    /// Loads the configuration file, or returns the defaults when there is none.
+    pub fn load() -> Result<Self> {
+        let Some(path) = paths::config_file("config.toml") else {
+            logging::debug!("No configuration file found.");
+            return Ok(Config::default());
+        };
+        logging::debug!("Loading configuration: {}", path.display());
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| Error::Usage(format!("Failed to read configuration {}: {}", path.display(), e)))?;
+        Config::parse(&text)
+            .map_err(|e| Error::Usage(format!("Invalid configuration {}: {}", path.display(), e)))
+    }
+
+    /// Parses the content of a configuration file.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut config = Config::default();
+        let mut table = String::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let at = |message: String| format!("line {}: {}", index + 1, message);
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with("[[") {
+                return Err(at("Arrays of tables are not supported".to_string()));
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                table = name.trim().to_string();
+                if !["relation", "wrap", "wrap.workspace", "wrap.scratchpad"].contains(&table.as_str()) {
+                    return Err(at(format!("Unknown table '{}'", table)));
+                }
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| at("Expected 'key = value'".to_string()))?;
+            let key = key.trim();
+            if key.contains('.') {
+                return Err(at(format!("Dotted key '{}' is not supported, use a [table] instead", key)));
+            }
+            let key = key.trim_matches('"');
+            let value = value.trim();
+            if let Some(message) = unsupported(value) {
+                return Err(at(format!("{} in the value of '{}'", message, key)));
+            }
+            let value = Value::parse(value)
+                .ok_or_else(|| at(format!("Invalid value of '{}'", key)))?;
+            config.set(&table, key, value).map_err(at)?;
+        }
+        Ok(config)
+    }
+
+    /// Returns how the command wraps when neither wrap nor nowrap is given, `None` if it doesn't.
+    pub fn wrap(&self, scope: &str, command: &str) -> Option<planar::Wrap> {
+        self.wrap.get(&format!("{} {}", scope, command)).copied()
+    }
+
+    fn set(&mut self, table: &str, key: &str, value: Value) -> std::result::Result<(), String> {
+        match (table, key) {
+            ("", "backend") => self.backend = Some(backend(value.string(key)?)?),
+            ("", "log-level") => {
+                let name = value.string(key)?;
+                let level = logging::Level::from_name(name)
+                    .ok_or(format!("Unknown log level '{}'", name))?;
+                self.log_level = Some(level);
+            }
+            ("", "history-depth") => {
+                let depth = usize::try_from(value.integer(key)?).ok()
+                    .filter(|depth| *depth > 1)
+                    .ok_or("Invalid 'history-depth', expected a number above 1")?;
+                self.history_depth = Some(depth);
+            }
+            ("", "linear") => {
+                self.navigation.linear = match value.string(key)? {
+                    "floating" => navigation::Linear::Floating,
+                    "tabs" => navigation::Linear::Tabs,
+                    name => return Err(format!("Unknown linear windows '{}', expected 'floating' or 'tabs'", name)),
+                };
+            }
+            ("", "fullscreen") => {
+                let name = value.string(key)?;
+                self.navigation.fullscreen = navigation::Fullscreen::from_name(name)
+                    .ok_or(format!("Unknown fullscreen policy '{}', expected 'stay', 'leave' or 'outputs'", name))?;
+            }
+            ("", "exclude") => self.navigation.exclude = rules(value.strings(key)?)?,
+            ("", "include") => self.navigation.include = rules(value.strings(key)?)?,
+            ("relation", "tiled") => self.navigation.tiled = relation(value.string(key)?)?,
+            ("relation", "floating") => self.navigation.floating = relation(value.string(key)?)?,
+            ("wrap", command) if WINDOW_WRAPS.contains(&command) => self.set_wrap("window", command, value)?,
+            ("wrap.workspace", command) if WORKSPACE_WRAPS.contains(&command) => self.set_wrap("workspace", command, value)?,
+            ("wrap.scratchpad", command) if SCRATCHPAD_WRAPS.contains(&command) => self.set_wrap("scratchpad", command, value)?,
+            ("", key) => return Err(format!("Unknown setting '{}'", key)),
+            (table, key) => return Err(format!("Unknown setting '{}' in '{}'", key, table)),
+        }
+        Ok(())
+    }
+
+    fn set_wrap(&mut self, scope: &str, command: &str, value: Value) -> std::result::Result<(), String> {
+        let wrap = match value {
+            Value::Bool(false) => None,
+            Value::Bool(true) => Some(planar::Wrap::Global),
+            Value::String(name) if name == "global" => Some(planar::Wrap::Global),
+            // Only directions know about outputs, the rest wraps just one way
+            Value::String(name) if name == "output" && DIRECTIONS.contains(&command) => Some(planar::Wrap::Output),
+            _ => return Err(format!("Invalid wrap of '{}'", command)),
+        };
+        let key = format!("{} {}", scope, command);
+        match wrap {
+            Some(wrap) => self.wrap.insert(key, wrap),
+            None => self.wrap.remove(&key),
+        };
+        Ok(())
+    }
+}
+
+impl Value {
+    /// Parses a quoted string, an integer, a boolean or an array of quoted strings.
+    fn parse(text: &str) -> Option<Value> {
+        match text {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ if text.starts_with('[') => parse_array(text).map(Value::Array),
+            _ if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') => {
+                text.replace('_', "").parse().ok().map(Value::Integer)
+            }
+            _ => parse_string(text).map(Value::String),
+        }
+    }
+
+    fn string(&self, key: &str) -> std::result::Result<&str, String> {
+        match self {
+            Value::String(string) => Ok(string),
+            _ => Err(format!("Expected a string for '{}'", key)),
+        }
+    }
+
+    fn integer(&self, key: &str) -> std::result::Result<i64, String> {
+        match self {
+            Value::Integer(integer) => Ok(*integer),
+            _ => Err(format!("Expected an integer for '{}'", key)),
+        }
+    }
+
+    fn strings(&self, key: &str) -> std::result::Result<&[String], String> {
+        match self {
+            Value::Array(strings) => Ok(strings),
+            _ => Err(format!("Expected an array of strings for '{}'", key)),
+        }
+    }
+}
+
+fn parse_string(text: &str) -> Option<String> {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"'))
+        .or_else(|| text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')))
+        .filter(|t| !t.contains(['"', '\'', '\\']))
+        .map(String::from)
+}
+
+/// Parses quoted strings separated by commas in brackets, strings may contain commas.
+fn parse_array(text: &str) -> Option<Vec<String>> {
+    let mut rest = text.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let mut strings = Vec::new();
+    while !rest.is_empty() {
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = rest[1..].find(quote)? + 2;
+        strings.push(parse_string(&rest[..end])?);
+        rest = rest[end..].trim_start();
+        if !rest.is_empty() {
+            rest = rest.strip_prefix(',')?.trim_start();
+        }
+    }
+    Some(strings)
+}
+
+/// Tells what valid TOML the value uses that isn't understood, if any.
+fn unsupported(value: &str) -> Option<&'static str> {
+    if value.starts_with('{') {
+        Some("Inline tables are not supported")
+    } else if value.starts_with('[') && !value.ends_with(']') {
+        Some("Arrays have to be on a single line")
+    } else if value.starts_with("\"\"\"") || value.starts_with("'''") {
+        Some("Multi-line strings are not supported")
+    } else if value.starts_with('"') && value.contains('\\') {
+        Some("Escapes are not supported")
+    } else {
+        None
+    }
+}
+
+/// Returns the line without the comment, which can't start inside of a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('#', None) => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn backend(name: &str) -> std::result::Result<UseBackend, String> {
+    match name {
+        "auto" => Ok(UseBackend::Auto),
+        #[cfg(feature = "i3")]
+        "i3" => Ok(UseBackend::I3),
+        #[cfg(feature = "wmctl")]
+        "wm" => Ok(UseBackend::WmCtl),
+        #[cfg(feature = "xcb")]
+        "xcb" => Ok(UseBackend::Xcb),
+        _ => Err(format!("Unknown or unavailable backend '{}'", name)),
+    }
+}
+
+fn rules(strings: &[String]) -> std::result::Result<Vec<Criteria>, String> {
+    strings.iter()
+        .map(|rule| Criteria::parse(rule).map_err(|e| format!("Invalid rule '{}': {}", rule, e)))
+        .collect()
+}
+
+fn relation(name: &str) -> std::result::Result<planar::Relation, String> {
+    match name {
+        "border" => Ok(planar::Relation::Border),
+        "center" => Ok(planar::Relation::Center),
+        _ => Err(format!("Unknown relation '{}', expected 'border' or 'center'", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_parse() {
+        let config = Config::parse("
+            # Comments and empty lines are skipped
+            log-level = 'warning'  # so are trailing comments
+            history-depth = 1_000
+            linear = \"tabs\"
+            fullscreen = \"outputs\"
+            exclude = [\"class=Conky\", 'title=Picture-in-Picture, floating=true',]
+            include = []
+
+            [relation]
+            floating = \"border\"
+
+            [wrap]
+            next = true
+            prev = true
+            left = \"output\"
+            prev = false
+
+            [wrap.workspace]
+            \"right\" = \"global\"
+
+            [wrap.scratchpad]
+            next = true
+        ").unwrap();
+        assert_eq!(config.backend, None);
+        assert_eq!(config.log_level, Some(logging::Level::WARNING));
+        assert_eq!(config.history_depth, Some(1000));
+        assert_eq!(config.navigation.linear, navigation::Linear::Tabs);
+        assert_eq!(config.navigation.fullscreen, navigation::Fullscreen::Outputs);
+        assert_eq!(config.navigation.exclude, vec![
+            Criteria { class: Some("Conky".to_string()), ..Default::default() },
+            Criteria { title: Some("Picture-in-Picture".to_string()), floating: Some(true), ..Default::default() },
+        ]);
+        assert!(config.navigation.include.is_empty());
+        assert_eq!(config.navigation.tiled, planar::Relation::Border);
+        assert_eq!(config.navigation.floating, planar::Relation::Border);
+        assert_eq!(config.wrap("window", "next"), Some(planar::Wrap::Global));
+        assert_eq!(config.wrap("window", "prev"), None);
+        assert_eq!(config.wrap("window", "left"), Some(planar::Wrap::Output));
+        assert_eq!(config.wrap("window", "right"), None);
+        assert_eq!(config.wrap("workspace", "right"), Some(planar::Wrap::Global));
+        assert_eq!(config.wrap("scratchpad", "next"), Some(planar::Wrap::Global));
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.navigation, navigation::Options::default());
+        assert_eq!(config.wrap("window", "next"), None);
+    }
+
+    #[test]
+    fn test_config_parse_errors() {
+        let error = |text: &str| Config::parse(text).unwrap_err();
+        assert_eq!(error("backend = \"dwm\""), "line 1: Unknown or unavailable backend 'dwm'");
+        assert_eq!(error("\n\nlog-level = true"), "line 3: Expected a string for 'log-level'");
+        assert_eq!(error("log-level = verbose"), "line 1: Invalid value of 'log-level'");
+        assert_eq!(error("[tabs]"), "line 1: Unknown table 'tabs'");
+        assert_eq!(error("[relation]\nfloating = \"corner\""), "line 2: Unknown relation 'corner', expected 'border' or 'center'");
+        assert_eq!(error("[wrap]\nnext = \"output\""), "line 2: Invalid wrap of 'next'");
+        assert_eq!(error("[wrap]\nnumber = true"), "line 2: Unknown setting 'number' in 'wrap'");
+        assert_eq!(error("[wrap.workspace]\nmru-next = true"), "line 2: Unknown setting 'mru-next' in 'wrap.workspace'");
+        assert_eq!(error("[wrap.scratchpad]\nleft = true"), "line 2: Unknown setting 'left' in 'wrap.scratchpad'");
+        assert_eq!(error("wrap"), "line 1: Expected 'key = value'");
+        assert_eq!(error("exclude = \"class=Conky\""), "line 1: Expected an array of strings for 'exclude'");
+        assert_eq!(error("exclude = [\"class=Conky\" \"type=dock\"]"), "line 1: Invalid value of 'exclude'");
+        assert_eq!(error("include = [\"class\"]"), "line 1: Invalid rule 'class': Expected 'key=value' in 'class'");
+        assert_eq!(error("fullscreen = \"exit\""), "line 1: Unknown fullscreen policy 'exit', expected 'stay', 'leave' or 'outputs'");
+        assert_eq!(error("group = true"), "line 1: Unknown setting 'group'");
+        assert_eq!(error("history-depth = \"16\""), "line 1: Expected an integer for 'history-depth'");
+        assert_eq!(error("history-depth = 1"), "line 1: Invalid 'history-depth', expected a number above 1");
+        assert_eq!(error("history-depth = 1.5"), "line 1: Invalid value of 'history-depth'");
+    }
+
+    /// Tests the valid TOML that isn't understood.
+    /// We expect it to be refused with its line and what isn't supported.
+    #[test]
+    fn test_config_parse_unsupported() {
+        let error = |text: &str| Config::parse(text).unwrap_err();
+        assert_eq!(error("[[wrap]]"), "line 1: Arrays of tables are not supported");
+        assert_eq!(error("\nwrap.next = true"), "line 2: Dotted key 'wrap.next' is not supported, use a [table] instead");
+        assert_eq!(error("wrap = { next = true }"), "line 1: Inline tables are not supported in the value of 'wrap'");
+        assert_eq!(error("exclude = [\n  \"class=Conky\",\n]"), "line 1: Arrays have to be on a single line in the value of 'exclude'");
+        assert_eq!(error("backend = '''xcb'''"), "line 1: Multi-line strings are not supported in the value of 'backend'");
+        assert_eq!(error("backend = \"\\u0078cb\""), "line 1: Escapes are not supported in the value of 'backend'");
+    }
+}
@@ -0,0 +1,255 @@
+//! Daemon mode keeps a single i3 connection open and the tree cached between invocations.
+//!
+//! Spawning `i3 --get-socketpath`, connecting and parsing the whole tree on every keypress is
+//! noticeable on big trees. The daemon does it once, then refreshes the tree on the first request
+//! after i3 reports window, workspace or output changes. Regular invocations forward their arguments over a Unix
+//! socket and exit with the code the daemon replies with. When no daemon is listening, they
+//! do the work themselves as before.
+
+use crate::backend::I3Backend;
+use crate::backend::traits::GetAll;
+use crate::backend::i3::Event;
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::history::History;
+use crate::logging;
+use crate::paths;
+use crate::urgency::Urgency;
+
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a client has to send its whole command. Requests are served one at a time, a client
+/// that never finishes its request must not hold up the others.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Ends every forwarded argument, so that empty arguments are kept.
+const TERMINATOR: char = '\0';
+
+/// Name of the socket the daemon listens on, in the runtime directory.
+const SOCKET: &str = "i3switch.sock";
+
+/// Returns the path of the socket the daemon listens on.
+pub fn socket_path() -> io::Result<PathBuf> {
+    Ok(paths::runtime_dir()?.join(SOCKET))
+}
+
+/// Forwards the command arguments to a running daemon.
+/// Returns the exit code replied by the daemon, or `None` if no daemon could handle the command,
+/// in which case the caller is expected to handle it by itself.
+pub fn forward(args: &[String]) -> Option<i32> {
+    let mut stream = UnixStream::connect(socket_path().ok()?).ok()?;
+    logging::debug!("Forwarding command to daemon: {:?}", args);
+    let reply = exchange(&mut stream, args);
+    if let Err(ref e) = reply {
+        logging::warning!("Daemon failed to handle the command: {}", e);
+    }
+    reply.ok()
+}
+
+/// Runs the daemon until i3 exits or the connection to it breaks, which ends the process.
+/// Forwarded commands are completed by the configuration loaded at the start, focus changes seen
+/// by the daemon are remembered in history as deep as given.
+/// Returns only when the daemon fails to start.
+pub fn serve(config: Config, history_depth: usize) -> Result<()> {
+    let runtime = paths::runtime_dir()
+        .map_err(|e| Error::Connection(format!("Failed to locate daemon socket: {}", e)))?;
+    let path = runtime.join(SOCKET);
+    let listener = bind(&path)
+        .map_err(|e| Error::Connection(format!("Failed to listen on daemon socket: {}", e)))?;
+    logging::info!("Daemon listening on: {}", path.display());
+
+    let backend = I3Backend::new()?;
+    let mut events = backend.subscribe()?;
+    let cache = Arc::new(Mutex::new(Cache { backend, outdated: false }));
+
+    // Keep track of what makes the cached tree outdated, the daemon has no purpose once i3 is
+    // gone, but it follows i3 restarting in place.
+    let watched = Arc::clone(&cache);
+    let watched_path = path.clone();
+    thread::spawn(move || {
+        loop {
+            match events.wait() {
+                Ok(None) => {
+                    logging::info!("i3 is exiting, stopping daemon.");
+                    break;
+                }
+                // i3 restarted in place, and closed the connection of the requests
+                Ok(Some((Event::Shutdown, _))) => {
+                    let mut cache = watched.lock().unwrap();
+                    cache.outdated = true;
+                    if let Err(e) = cache.backend.reconnect() {
+                        logging::warning!("Failed to reconnect to restarted i3: {}", e);
+                    }
+                }
+                Ok(Some((event, window_event))) => {
+                    logging::debug!("Tree outdated by event: {:?} {:?}", event, window_event);
+                    let mut cache = watched.lock().unwrap();
+                    cache.outdated = true;
+                    let Some(window_event) = window_event else {
+                        continue;
+                    };
+                    match window_event.change.as_str() {
+                        // Focus changes made outside of i3switch are only seen by the daemon
+                        "focus" => {
+                            let mut history = History::load(&runtime);
+                            history.focus(window_event.container.id, history_depth);
+                            history.save(&runtime);
+                        }
+                        // So are the windows becoming urgent, in the order they do
+                        "urgent" => {
+                            if let Some(backend) = cache.current() {
+                                update_urgency(backend, &runtime);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Err(e) => {
+                    logging::error!("Lost connection to i3 events: {}", e);
+                    break;
+                }
+            }
+        }
+        let _ = std::fs::remove_file(&watched_path);
+        std::process::exit(0);
+    });
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let mut cache = cache.lock().unwrap();
+                // Without a reply, the client handles the command by itself.
+                if let Some(backend) = cache.current() {
+                    if let Err(e) = handle(&mut stream, backend, &config) {
+                        logging::warning!("Failed to handle daemon request: {}", e);
+                    }
+                }
+                // The events will come, but the next request could be faster.
+                cache.outdated = true;
+            }
+            Err(e) => {
+                logging::warning!("Failed to accept daemon connection: {}", e);
+            }
+        }
+    }
+    unreachable!("Daemon listener stopped accepting connections.");
+}
+
+/// The i3 tree, refreshed only when it's needed after it got outdated.
+struct Cache {
+    backend: I3Backend,
+    outdated: bool,
+}
+
+impl Cache {
+    /// Returns the backend with a current tree, or `None` if it can't be refreshed.
+    fn current(&mut self) -> Option<&mut I3Backend> {
+        if self.outdated {
+            logging::debug!("Refreshing outdated tree.");
+            if let Err(e) = self.backend.refresh() {
+                logging::warning!("Failed to refresh tree: {}", e);
+                return None;
+            }
+            self.outdated = false;
+        }
+        Some(&mut self.backend)
+    }
+}
+
+/// Catches up the order of urgent windows saved in the runtime directory with the cached tree.
+fn update_urgency(backend: &I3Backend, runtime: &Path) {
+    let Ok(windows) = backend.get_all() else {
+        return;
+    };
+    let urgent: Vec<u64> = windows.iter().filter(|w| w.urgent).map(|w| w.id).collect();
+    let mut urgency = Urgency::load(runtime);
+    if urgency.update(&urgent) {
+        urgency.save(runtime);
+    }
+}
+
+/// Binds the daemon socket, replacing a stale socket file left by a daemon that didn't exit
+/// cleanly, but refusing to replace one that is still served.
+fn bind(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, "Daemon is already running"));
+        }
+        std::fs::remove_file(path)?;
+    }
+    UnixListener::bind(path)
+}
+
+/// Reads forwarded arguments from the stream, executes the command and replies with the exit
+/// code.
+fn handle(stream: &mut UnixStream, backend: &mut I3Backend, config: &Config) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = String::new();
+    stream.read_to_string(&mut request)?;
+    if request.is_empty() {
+        // Liveness probe of another daemon trying to start, see `bind`.
+        return Ok(());
+    }
+
+    let mut args = vec!["i3switch".to_string()];
+    args.extend(parse_request(&request));
+    logging::info!("Daemon received command: {:?}", &args[1..]);
+
+    let code = match Cli::try_parse(args, config) {
+        Ok(cli) if cli.command == "daemon" => {
+            logging::warning!("Daemon is already running.");
+            Error::Usage("Daemon is already running".to_string()).exit_code()
+        }
+        Ok(cli) => crate::app::execute(&cli, backend),
+        Err(message) => {
+            logging::error!("Invalid forwarded command: {}", message);
+            Error::Usage(message).exit_code()
+        }
+    };
+    stream.write_all(code.to_string().as_bytes())
+}
+
+/// Splits the request into the arguments, each of them ended by the terminator.
+fn parse_request(request: &str) -> Vec<String> {
+    let request = request.strip_suffix(TERMINATOR).unwrap_or(request);
+    request.split(TERMINATOR).map(String::from).collect()
+}
+
+/// Joins the arguments into a request, ending each of them by the terminator.
+fn format_request(args: &[String]) -> String {
+    args.iter().map(|arg| format!("{}{}", arg, TERMINATOR)).collect()
+}
+
+/// Sends the arguments over the stream and waits for the exit code.
+fn exchange(stream: &mut UnixStream, args: &[String]) -> io::Result<i32> {
+    stream.write_all(format_request(args).as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    reply.trim().parse::<i32>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_keeps_empty_arguments() {
+        let args: Vec<String> = ["raise-or-run", "--title", "", "--", "term"]
+            .iter().map(|a| a.to_string()).collect();
+        assert_eq!(parse_request(&format_request(&args)), args);
+
+        let args = vec!["find".to_string(), String::new()];
+        assert_eq!(format_request(&args), "find\0\0");
+        assert_eq!(parse_request("find\0\0"), args);
+    }
+}
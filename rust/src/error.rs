@@ -0,0 +1,112 @@
+//! Errors of i3switch, telling apart what went wrong so that callers can react to it.
+//!
+//! Finding nothing to switch to is not an error, navigation returns `None` for it. Every error
+//! maps to its own exit code, keybinding scripts can tell a broken socket from an operation the
+//! backend doesn't support:
+//!
+//! | Code | Meaning                                                         |
+//! |------|-----------------------------------------------------------------|
+//! | 0    | Done, or the report was printed                                 |
+//! | 1    | Nothing to switch to, no window or workspace in that direction |
+//! | 2    | Invalid arguments                                               |
+//! | 3    | Connection to the window manager failed or broke                |
+//! | 4    | Reply of the window manager could not be understood             |
+//! | 5    | Window manager refused or failed to carry out the command       |
+//! | 6    | Operation not supported by the backend                          |
+//! | 7    | Window or workspace doesn't exist anymore                       |
+//! | 8    | Command to run could not be started                             |
+
+use std::fmt;
+use std::io;
+
+/// Exit code of a command that did what was asked.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code of a command that found nothing to switch to.
+pub const EXIT_NOTHING: i32 = 1;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The arguments can't be understood.
+    Usage(String),
+    /// The window manager can't be reached, or the connection to it broke.
+    Connection(String),
+    /// The window manager replied with something that can't be understood.
+    Protocol(String),
+    /// The window manager refused or failed to carry out the command.
+    Command(String),
+    /// The backend doesn't support the operation.
+    Unsupported(String),
+    /// The window or workspace doesn't exist, it might have been closed meanwhile.
+    Missing(String),
+    /// The command to run couldn't be started.
+    Run(String),
+}
+
+impl Error {
+    /// Returns the process exit code documented for the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::Connection(_) => 3,
+            Error::Protocol(_) => 4,
+            Error::Command(_) => 5,
+            Error::Unsupported(_) => 6,
+            Error::Missing(_) => 7,
+            Error::Run(_) => 8,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(message)
+            | Error::Connection(message)
+            | Error::Protocol(message)
+            | Error::Command(message)
+            | Error::Unsupported(message)
+            | Error::Missing(message)
+            | Error::Run(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Socket errors are broken connections, unless the data received over it was invalid.
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::InvalidData => Error::Protocol(error.to_string()),
+            _ => Error::Connection(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let errors = [
+            Error::Usage(String::new()),
+            Error::Connection(String::new()),
+            Error::Protocol(String::new()),
+            Error::Command(String::new()),
+            Error::Unsupported(String::new()),
+            Error::Missing(String::new()),
+            Error::Run(String::new()),
+        ];
+        let codes: Vec<i32> = errors.iter().map(Error::exit_code).collect();
+        assert_eq!(codes, vec![2, 3, 4, 5, 6, 7, 8]);
+        assert!(!codes.contains(&EXIT_SUCCESS) && !codes.contains(&EXIT_NOTHING));
+
+        let error = Error::from(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic number"));
+        assert_eq!(error, Error::Protocol("Invalid magic number".to_string()));
+        let error = Error::from(io::Error::from(io::ErrorKind::ConnectionRefused));
+        assert_eq!(error.exit_code(), 3);
+    }
+}
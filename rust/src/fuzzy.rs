@@ -0,0 +1,108 @@
+//! Fuzzy matching of window properties, to find windows by what they are instead of where.
+//!
+//! A query matches a text when all its characters appear in the text in the same order, ignoring
+//! case. Exact, prefix and substring matches score above scattered ones, and among scattered
+//! ones, consecutive characters and word starts score above the rest.
+
+use crate::types::Properties;
+
+/// Scores how well the query matches the text, `None` if it doesn't match at all.
+pub fn score(query: &str, text: &str) -> Option<u32> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    if query.is_empty() || query.len() > text.len() {
+        return None;
+    }
+    if query == text {
+        return Some(1000);
+    }
+    if text.starts_with(&query) {
+        return Some(900 - penalty(text.len() - query.len()));
+    }
+    if let Some(position) = text.windows(query.len()).position(|w| w == query.as_slice()) {
+        let base = if is_word_start(&text, position) { 800 } else { 700 };
+        return Some(base - penalty(position));
+    }
+
+    // Scattered characters, each one scores by how it continues the match
+    let mut score = 100;
+    let mut last: Option<usize> = None;
+    let mut from = 0;
+    for c in query {
+        let position = from + text[from..].iter().position(|t| *t == c)?;
+        score += if last.is_some_and(|l| l + 1 == position) {
+            10
+        } else if is_word_start(&text, position) {
+            8
+        } else {
+            1
+        };
+        last = Some(position);
+        from = position + 1;
+    }
+    Some(score.min(499))
+}
+
+/// Scores how well the query matches any of the window properties. Marks given by the user win,
+/// then names of the application, and the title, which changes with the content, comes last.
+pub fn score_properties(query: &str, properties: &Properties) -> Option<u32> {
+    let names = [
+        (properties.class.as_deref(), 200),
+        (properties.app_id.as_deref(), 200),
+        (properties.instance.as_deref(), 150),
+        (properties.title.as_deref(), 0),
+    ];
+    let marks = properties.marks.iter().map(|m| (Some(m.as_str()), 300));
+    marks.chain(names)
+        .filter_map(|(text, weight)| Some(score(query, text?)? + weight))
+        .max()
+}
+
+/// Penalty for the distance from the ideal match, bounded so that it never drops a score into
+/// the range of a worse kind of match.
+fn penalty(distance: usize) -> u32 {
+    distance.min(99) as u32
+}
+
+fn is_word_start(text: &[char], position: usize) -> bool {
+    position == 0 || !text[position - 1].is_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("firefox", "Firefox"), Some(1000));
+        assert_eq!(score("fire", "firefox"), Some(897));
+        assert_eq!(score("fox", "Firefox"), Some(696));
+        assert_eq!(score("vim", "~/src - vim"), Some(792));
+        assert!(score("ffx", "firefox").is_some());
+        assert!(score("ffx", "firefox") < score("fox", "firefox"));
+        assert!(score("st", "sublime text") > score("st", "sublime hot"));
+        assert_eq!(score("xf", "firefox"), None);
+        assert_eq!(score("", "firefox"), None);
+        assert_eq!(score("firefoxes", "firefox"), None);
+    }
+
+    #[test]
+    fn test_score_properties() {
+        let terminal = Properties {
+            class: Some("Alacritty".to_string()),
+            instance: Some("alacritty".to_string()),
+            title: Some("vim notes.md".to_string()),
+            ..Default::default()
+        };
+        let editor = Properties {
+            app_id: Some("org.gnome.TextEditor".to_string()),
+            title: Some("notes.md".to_string()),
+            marks: vec!["notes".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(score_properties("alacritty", &terminal), Some(1200));
+        assert!(score_properties("notes", &editor) > score_properties("notes", &terminal));
+        assert!(score_properties("editor", &editor).is_some());
+        assert_eq!(score_properties("firefox", &terminal), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Most recently used window history, kept between invocations for alt-tab like switching.
+//!
+//! Focusing a window puts it in front of the history. Stepping through the history moves a
+//! cursor instead, so the order stays the same until the focus lands somewhere else, the same
+//! way alt-tab keeps the order while alt is held.
+
+use crate::linear;
+use crate::logging;
+
+use std::path::Path;
+
+/// Name of the file the history is saved in, in the runtime directory.
+const FILE: &str = "i3switch.history";
+
+/// Number of windows remembered, unless configured otherwise.
+pub const DEFAULT_DEPTH: usize = 16;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct History {
+    windows: Vec<u64>,
+    cursor: usize,
+}
+
+impl History {
+    pub fn new(windows: Vec<u64>, cursor: usize) -> Self {
+        let cursor = if cursor < windows.len() { cursor } else { 0 };
+        Self { windows, cursor }
+    }
+
+    /// Loads the history saved in the runtime directory by previous invocations, or an empty one
+    /// if there is none.
+    pub fn load(dir: &Path) -> Self {
+        match std::fs::read_to_string(dir.join(FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => {
+                logging::debug!("No focus history found, starting a new one.");
+                Self::default()
+            }
+        }
+    }
+
+    /// Saves the history in the runtime directory for the next invocations.
+    pub fn save(&self, dir: &Path) {
+        if let Err(e) = std::fs::write(dir.join(FILE), self.serialize()) {
+            logging::warning!("Failed to save focus history: {}", e);
+        }
+    }
+
+    /// Records that the window got focus.
+    /// Focus on the window under the cursor is the result of stepping through the history and
+    /// doesn't change the order.
+    pub fn focus(&mut self, window_id: u64, depth: usize) {
+        if self.windows.get(self.cursor) == Some(&window_id) {
+            return;
+        }
+        self.windows.retain(|id| *id != window_id);
+        self.windows.insert(0, window_id);
+        self.windows.truncate(depth);
+        self.cursor = 0;
+    }
+
+    /// Puts the window under the cursor in front, ending the stepping through the history.
+    pub fn commit(&mut self) {
+        if self.cursor > 0 {
+            let window_id = self.windows.remove(self.cursor);
+            self.windows.insert(0, window_id);
+            self.cursor = 0;
+        }
+    }
+
+    /// Moves the cursor to the window, if it is in the history.
+    pub fn select(&mut self, window_id: u64) {
+        if let Some(index) = self.windows.iter().position(|id| *id == window_id) {
+            self.cursor = index;
+        }
+    }
+
+    /// Forgets the windows that no longer exist.
+    pub fn prune(&mut self, existing: &[u64]) {
+        let current = self.windows.get(self.cursor).copied();
+        self.windows.retain(|id| existing.contains(id));
+        self.cursor = current
+            .and_then(|current| self.windows.iter().position(|id| *id == current))
+            .unwrap_or(0);
+    }
+
+    /// Returns the history as a sequence, from the most recent window, positioned at the cursor.
+    pub fn sequence(&self) -> linear::Sequence {
+        linear::Sequence::new(self.windows.clone(), self.cursor)
+    }
+
+    /// Parses the cursor from the first line and window ids from the following ones.
+    fn parse(content: &str) -> Self {
+        let mut lines = content.lines();
+        let cursor = lines.next().and_then(|l| l.trim().parse::<usize>().ok()).unwrap_or(0);
+        let windows = lines.filter_map(|l| l.trim().parse::<u64>().ok()).collect();
+        Self::new(windows, cursor)
+    }
+
+    fn serialize(&self) -> String {
+        let mut content = format!("{}\n", self.cursor);
+        self.windows.iter().for_each(|id| content.push_str(&format!("{}\n", id)));
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus() {
+        let mut history = History::default();
+        history.focus(1, 3);
+        history.focus(2, 3);
+        history.focus(3, 3);
+        assert_eq!(history, History::new(vec![3, 2, 1], 0));
+
+        history.focus(1, 3);
+        assert_eq!(history, History::new(vec![1, 3, 2], 0));
+
+        history.focus(4, 3);
+        assert_eq!(history, History::new(vec![4, 1, 3], 0));
+
+        // Focus on the window under the cursor keeps the order while stepping through
+        history.select(3);
+        history.focus(3, 3);
+        assert_eq!(history, History::new(vec![4, 1, 3], 2));
+
+        // Focus elsewhere ends stepping through
+        history.focus(5, 3);
+        assert_eq!(history, History::new(vec![5, 4, 1], 0));
+    }
+
+    #[test]
+    fn test_commit() {
+        let mut history = History::new(vec![1, 2, 3], 2);
+        history.commit();
+        assert_eq!(history, History::new(vec![3, 1, 2], 0));
+
+        history.commit();
+        assert_eq!(history, History::new(vec![3, 1, 2], 0));
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut history = History::new(vec![1, 2, 3, 4], 2);
+        history.prune(&[2, 3, 5]);
+        assert_eq!(history, History::new(vec![2, 3], 1));
+
+        history.prune(&[2]);
+        assert_eq!(history, History::new(vec![2], 0));
+    }
+
+    #[test]
+    fn test_sequence() {
+        let history = History::new(vec![1, 2, 3], 1);
+        let sequence = history.sequence();
+        assert_eq!(sequence.next(linear::Direction::Next), Some(3));
+        assert_eq!(sequence.next(linear::Direction::Prev), Some(1));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let history = History::new(vec![10, 20, 30], 1);
+        assert_eq!(History::parse(&history.serialize()), history);
+        assert_eq!(History::parse(""), History::default());
+        assert_eq!(History::parse("7\n1\n"), History::new(vec![1], 0));
+    }
+}
@@ -0,0 +1,66 @@
+//! i3switch switches the focus to what You see, by the position of windows on the screen instead
+//! of the tiling tree of the window manager.
+//!
+//! The navigation works with any source of windows. Implement the traits of [`backend`] for it,
+//! and ask [`navigation`] where to switch:
+//!
+//! ```
+//! use i3switch::backend::{GetGroups, GetOutputs, GetVisible};
+//! use i3switch::error::{Error, Result};
+//! use i3switch::navigation;
+//! use i3switch::planar::Direction;
+//! use i3switch::types::{Outputs, Rect, Window, Windows};
+//!
+//! struct Screen(Windows);
+//!
+//! impl GetVisible for Screen {
+//!     fn get_visible(&self) -> Result<Windows> {
+//!         Ok(self.0.clone())
+//!     }
+//! }
+//!
+//! impl GetGroups for Screen {
+//!     fn get_groups(&self) -> Result<Windows> {
+//!         self.get_visible()
+//!     }
+//! }
+//!
+//! impl GetOutputs for Screen {
+//!     fn get_outputs(&self) -> Result<Outputs> {
+//!         Err(Error::Unsupported("Outputs not supported".to_string()))
+//!     }
+//! }
+//!
+//! let window = |id, x, focused| Window {
+//!     id, rect: Rect { x, y: 0, w: 100, h: 100 }, focused, ..Default::default()
+//! };
+//! let screen = Screen(vec![window(1, 0, true), window(2, 100, false)]);
+//! let options = navigation::Options::default();
+//! let target = navigation::get_window_in_direction(&screen, &options, Direction::Right, None, false);
+//! assert_eq!(target, Ok(Some(2)));
+//! ```
+//!
+//! The `i3switch` binary is a thin wrapper of [`run`].
+
+#![recursion_limit = "256"] // Required for tests with older serde_json
+
+pub mod backend;
+pub mod error;
+pub mod history;
+pub mod linear;
+pub mod navigation;
+pub mod planar;
+pub mod types;
+pub mod urgency;
+
+mod app;
+mod cli;
+mod config;
+#[cfg(feature = "i3")]
+mod daemon;
+mod fuzzy;
+mod logging;
+mod paths;
+mod query;
+
+pub use app::run;
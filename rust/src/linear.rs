@@ -35,6 +35,10 @@ impl Sequence {
     pub fn size(&self) -> usize {
         self.items.len()
     }
+
+    pub fn items(&self) -> &[u64] {
+        &self.items
+    }
 }
 
 impl Index<usize> for Sequence {
@@ -1,13 +1,16 @@
-/// A simple logging module for Rust with custom macros and context information.
-/// Logging follows the format inspired by glibc based desktop applications,
-/// especially i3 window manager.
-///
-/// The choice is for a consistent logging style in the environment.
-///
-/// For example, a debug log would look like:
-/// i3switch: DEBUG: file.rs:123: Entering function
+//! A simple logging module for Rust with custom macros and context information.
+//! Logging follows the format inspired by glibc based desktop applications,
+//! especially i3 window manager.
+//!
+//! The choice is for a consistent logging style in the environment.
+//!
+//! For example, a debug log would look like:
+//! i3switch: DEBUG: file.rs:123: Entering function
 
-#[derive(Debug)]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
     DEBUG,
     INFO,
@@ -15,23 +18,42 @@ pub enum Level {
     ERROR,
 }
 
-#[macro_export]
-macro_rules! log {
-    ($level:expr, $message:expr) => {{
-        #[cfg(debug_assertions)]
-        println!("i3switch: [{:?}] {}:{}: {}", $level, file!(), line!(), $message);
-        #[cfg(not(debug_assertions))]
-        println!("i3switch: {:?}: {}", $level, $message);
-    }};
+impl Level {
+    /// Returns the level of the name used in the configuration and on the command line.
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "debug" => Some(Level::DEBUG),
+            "info" => Some(Level::INFO),
+            "warning" => Some(Level::WARNING),
+            "error" => Some(Level::ERROR),
+            _ => None,
+        }
+    }
 }
 
-#[macro_export]
-macro_rules! elog {
+/// Messages below this level are not printed, only warnings and errors are unless configured
+/// otherwise.
+static LEVEL: AtomicU8 = AtomicU8::new(Level::WARNING as u8);
+
+/// Sets the lowest level of messages that get printed.
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns whether messages of the level get printed.
+pub fn enabled(level: Level) -> bool {
+    level as u8 >= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Prints the message of the level to stderr, stdout is left to the output of the commands.
+macro_rules! log {
     ($level:expr, $message:expr) => {{
-        #[cfg(debug_assertions)]
-        eprintln!("i3switch: [{:?}] {}:{}: {}", $level, file!(), line!(), $message);
-        #[cfg(not(debug_assertions))]
-        eprintln!("i3switch: {:?}: {}", $level, $message);
+        if $crate::logging::enabled($level) {
+            #[cfg(debug_assertions)]
+            eprintln!("i3switch: [{:?}] {}:{}: {}", $level, file!(), line!(), $message);
+            #[cfg(not(debug_assertions))]
+            eprintln!("i3switch: {:?}: {}", $level, $message);
+        }
     }};
 }
 
@@ -55,37 +77,10 @@ macro_rules! warning {
 
 macro_rules! error {
     ($($arg:tt)*) => {
-        $crate::logging::elog!($crate::logging::Level::ERROR, format!($($arg)*));
+        $crate::logging::log!($crate::logging::Level::ERROR, format!($($arg)*));
     };
 }
 
-macro_rules! critical {
-    ($($arg:tt)*) => {
-        $crate::logging::elog!($crate::logging::Level::ERROR, format!($($arg)*));
-        panic!("Critical error encountered, terminating program.");
-    };
-}
-
-pub trait ResultExt<T, E> {
-    #[allow(dead_code)]
-    fn expect_log(self, msg: &str) -> T;
-}
-
-impl<T, E> ResultExt<T, E> for Result<T, E>
-where
-    E: std::fmt::Display,
-{
-    fn expect_log(self, msg: &str) -> T {
-        match self {
-            Ok(value) => value,
-            Err(e) => {
-                crate::logging::error!("{}: {}", msg, e);
-                panic!("{}", msg);
-            }
-        }
-    }
-}
-
 pub trait OptionExt<T> {
     #[allow(dead_code)]
     fn wanted(self, msg: &str) -> Option<T>;
@@ -104,9 +99,7 @@ impl<T> OptionExt<T> for Option<T> {
 }
 
 pub (super) use log;
-pub (super) use elog;
 pub (super) use info;
 pub (super) use warning;
 pub (super) use error;
 pub (super) use debug;
-pub (super) use critical;
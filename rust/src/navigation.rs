@@ -1,9 +1,86 @@
-use crate::backend::traits::{GetVisible, GetTabs};
+use crate::backend::traits::{GetAll, GetContainers, GetGroups, GetOutputs, GetScratchpad, GetVisible, GetTabs, GetWorkspaces};
+use crate::error::Result;
+use crate::fuzzy;
+use crate::history::History;
 use crate::linear;
 use crate::logging::OptionExt;
 use crate::logging;
 use crate::planar;
-use crate::types::Windows;
+use crate::urgency::Urgency;
+use crate::types::{Criteria, Rect, TabLevel, Window, Windows};
+
+/// Windows navigated by next, prev and number while a floating window is focused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linear {
+    /// The floating windows, from left to right.
+    Floating,
+    /// The tabs of the workspace, as if no floating window was focused.
+    Tabs,
+}
+
+/// Where directions lead while a fullscreen window is focused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fullscreen {
+    /// Nowhere, the fullscreen window keeps the focus.
+    Stay,
+    /// To the windows behind it, after leaving fullscreen.
+    Leave,
+    /// To the fullscreen windows on the other outputs.
+    Outputs,
+}
+
+impl Fullscreen {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stay" => Some(Fullscreen::Stay),
+            "leave" => Some(Fullscreen::Leave),
+            "outputs" => Some(Fullscreen::Outputs),
+            _ => None,
+        }
+    }
+}
+
+/// Preferences of how windows are navigated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Relation of tiled windows in directions.
+    pub tiled: planar::Relation,
+    /// Relation of floating windows in directions.
+    pub floating: planar::Relation,
+    /// Windows navigated linearly while a floating window is focused.
+    pub linear: Linear,
+    /// Tabbed or stacked container navigated linearly, when they are nested.
+    pub tabs: TabLevel,
+    /// Windows navigated in directions while a fullscreen window is focused.
+    pub fullscreen: Fullscreen,
+    /// Windows never switched to, like overlays or picture-in-picture videos.
+    pub exclude: Vec<Criteria>,
+    /// Exceptions to the excluded windows.
+    pub include: Vec<Criteria>,
+}
+
+impl Options {
+    /// Returns whether the window can be switched to, it is not excluded or it is included
+    /// again.
+    pub fn is_navigable(&self, window: &Window) -> bool {
+        !self.exclude.iter().any(|criteria| criteria.matches(window))
+            || self.include.iter().any(|criteria| criteria.matches(window))
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            tiled: planar::Relation::Border,
+            floating: planar::Relation::Center,
+            linear: Linear::Floating,
+            tabs: TabLevel::default(),
+            fullscreen: Fullscreen::Stay,
+            exclude: Vec::new(),
+            include: Vec::new(),
+        }
+    }
+}
 
 // --------------------------------------
 // Public functions for window navigation
@@ -12,54 +89,247 @@ use crate::types::Windows;
 /// Get window to switch to in tabbed, stacked or floating layout.
 /// If `wrap` is true, it will wrap around to the first/last window if no next/previous window is
 /// available.
-pub fn get_window_to_switch_to<B: GetVisible + GetTabs>(backend: &B, direction: linear::Direction, wrap: bool) -> u64 {
-    let sequence = get_linear_sequence(backend);
-    if let Some(window_id) = sequence.next(direction) {
-        window_id
-    } else if wrap {
-        if let Some(window_id) = sequence.first(direction) {
-            window_id
-        } else {
-            logging::error!("No windows available to switch to.");
-            std::process::exit(1);
-        }
-    } else {
+/// Returns `None` if there is no window to switch to.
+pub fn get_window_to_switch_to<B: GetVisible + GetTabs>(backend: &B, options: &Options, direction: linear::Direction, wrap: bool) -> Result<Option<u64>> {
+    let sequence = get_linear_sequence(backend, options)?;
+    let window_id = match sequence.next(direction) {
+        None if wrap => sequence.first(direction),
+        window_id => window_id,
+    };
+    if window_id.is_none() {
         logging::info!("No windows available to switch to.");
-        std::process::exit(0);
     }
+    Ok(window_id)
 }
 
-/// Get window to switch based on their position in the planar arrangement.
-/// If `wrap` is true, it will wrap around to the first/last window if no next/previous window is
-/// available.
-/// If no window is available in the specified direction, it will print an error message and exit
-/// the program.
-/// If `wrap` is false, it will print an info message and exit the program without switching.
-pub fn get_window_in_direction<B: GetVisible>(backend: &B, direction: planar::Direction, wrap: bool) -> u64 {
-    let mut arrangement = get_planar_arrangement(backend);
-    if let Some(window) = arrangement.next(direction) {
-        window.id
-    } else if wrap {
-        if let Some(window) = arrangement.first(direction) {
-            window.id
-        } else {
-            logging::error!("No windows available to switch to.");
-            std::process::exit(1);
-        }
-    } else {
+/// Get window to switch based on their position in the planar layout.
+/// The output of the focused window is searched first, then the neighbouring output in the
+/// direction.
+/// If `wrap` is set, it will wrap around to the first window of the current output or of the
+/// whole layout if no next window is available.
+/// If `group` is true, tabbed and stacked containers are treated as a single window.
+/// If no window is available in the specified direction, it will log a message and return `None`.
+pub fn get_window_in_direction<B>(backend: &B, options: &Options, direction: planar::Direction, wrap: Option<planar::Wrap>, group: bool) -> Result<Option<u64>>
+where
+    B: GetVisible + GetGroups + GetOutputs,
+{
+    let layout = get_planar_layout(backend, options, group)?;
+    let window_id = layout.next(direction, wrap).map(|w| w.id);
+    if window_id.is_none() {
         logging::info!("No windows available to switch to.");
-        std::process::exit(0);
     }
+    Ok(window_id)
 }
 
 /// Get the window ID of a specific window number for tabbed, stacked and floating layouts.
-/// If the number is out of bounds, it will print an error message and exit the program.
-pub fn get_window_of_number<B: GetVisible + GetTabs>(backend: &B, number: usize) -> u64 {
-    let sequence = get_linear_sequence(backend);
+/// If the number is out of bounds, it will log a message and return `None`.
+pub fn get_window_of_number<B: GetVisible + GetTabs>(backend: &B, options: &Options, number: usize) -> Result<Option<u64>> {
+    let sequence = get_linear_sequence(backend, options)?;
+    if number >= sequence.size() {
+        logging::info!("No window number: {}. There are only {} windows available.", number, sequence.size());
+        return Ok(None);
+    }
+    Ok(Some(sequence[number]))
+}
+
+/// Get the window focused before the current one, so that repeating it alternates between the
+/// two most recently used windows.
+pub fn get_previous_window<B: GetAll>(backend: &B, options: &Options, history: &mut History) -> Result<Option<u64>> {
+    prune_history(backend, options, history);
+    history.commit();
+    let window_id = history.sequence().next(linear::Direction::Next);
+    if window_id.is_none() {
+        logging::info!("No previous window in focus history.");
+    }
+    Ok(window_id)
+}
+
+/// Get window to switch to by stepping through the focus history, from the most recently used.
+/// The history keeps its order while stepping through it, so repeating it reaches older windows.
+/// If `wrap` is true, it will wrap around to the other end of the history.
+pub fn get_window_in_history<B: GetAll>(backend: &B, options: &Options, history: &mut History, direction: linear::Direction, wrap: bool) -> Result<Option<u64>> {
+    prune_history(backend, options, history);
+    let sequence = history.sequence();
+    let window_id = match sequence.next(direction) {
+        None if wrap => sequence.first(direction),
+        window_id => window_id,
+    };
+    match window_id {
+        Some(window_id) => history.select(window_id),
+        None => {
+            logging::info!("No windows available to switch to in focus history.");
+        }
+    }
+    Ok(window_id)
+}
+
+/// Get the window best matching the query by its properties, among all windows.
+/// Matches are ordered from the best one. When the focused window is one of them, the one after
+/// it is returned, so that repeating the same query cycles through all the matches.
+/// Returns `None` if no window matches.
+pub fn get_window_matching<B: GetAll>(backend: &B, options: &Options, query: &str) -> Result<Option<u64>> {
+    let windows = backend.get_all()?;
+    let mut matches: Vec<(u32, &Window)> = windows.iter()
+        .filter(|w| options.is_navigable(w))
+        .filter_map(|w| Some((fuzzy::score_properties(query, &w.properties)?, w)))
+        .collect();
+    // Sorting is stable, equal matches keep the order of the backend between invocations
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    logging::debug!("Windows matching '{}': {:?}", query,
+        matches.iter().map(|(score, w)| (w.id, *score)).collect::<Vec<_>>());
+
+    let window_id = next_match(&matches.into_iter().map(|(_, w)| w).collect::<Vec<&Window>>());
+    if window_id.is_none() {
+        logging::info!("No windows matching '{}'.", query);
+    }
+    Ok(window_id)
+}
+
+/// Get the window meeting the criteria among all windows, including hidden ones.
+/// When the focused window meets them, the next one is returned, cycling through all of them.
+/// Returns `None` if no window meets the criteria.
+pub fn get_window_to_raise<B: GetAll>(backend: &B, options: &Options, criteria: &Criteria) -> Result<Option<u64>> {
+    let windows = backend.get_all()?;
+    let matches: Vec<&Window> = windows.iter()
+        .filter(|w| options.is_navigable(w) && criteria.matches(w))
+        .collect();
+    let window_id = next_match(&matches);
+    if window_id.is_none() {
+        logging::info!("No windows meeting criteria: {:?}", criteria);
+    }
+    Ok(window_id)
+}
+
+/// Get the urgent window waiting for the longest time, among all windows.
+/// When the focused window is still urgent, the next one is returned, cycling through all urgent
+/// windows. The order of urgency is caught up with the backend on the way.
+/// Returns `None` if there is no urgent window.
+pub fn get_urgent_window<B: GetAll>(backend: &B, options: &Options, urgency: &mut Urgency) -> Result<Option<u64>> {
+    let windows = backend.get_all()?;
+    let urgent: Vec<u64> = windows.iter().filter(|w| w.urgent).map(|w| w.id).collect();
+    urgency.update(&urgent);
+
+    let matches: Vec<&Window> = urgency.windows().iter()
+        .filter_map(|id| windows.iter().find(|w| w.id == *id))
+        .filter(|w| options.is_navigable(w))
+        .collect();
+    let window_id = next_match(&matches);
+    if window_id.is_none() {
+        logging::info!("No urgent windows.");
+    }
+    Ok(window_id)
+}
+
+/// Get the container enclosing the focused window or container.
+/// Returns `None` if there is none, like for the workspace or floating windows.
+pub fn get_parent_container<B: GetContainers>(backend: &B) -> Result<Option<u64>> {
+    let container_id = backend.get_parent()?;
+    if container_id.is_none() {
+        logging::info!("No container encloses the focused one.");
+    }
+    Ok(container_id)
+}
+
+/// Get the child of the focused container it was left by, the way back down from the parent.
+/// Returns `None` if a window is focused.
+pub fn get_child_container<B: GetContainers>(backend: &B) -> Result<Option<u64>> {
+    let container_id = backend.get_child()?;
+    if container_id.is_none() {
+        logging::info!("No child of the focused container.");
+    }
+    Ok(container_id)
+}
+
+/// Get the focused window if it is fullscreen.
+pub fn get_fullscreen_window<B: GetVisible>(backend: &B) -> Result<Option<u64>> {
+    let windows = backend.get_visible()?;
+    Ok(windows.iter().find(|w| w.focused && w.fullscreen).map(|w| w.id))
+}
+
+/// Get the currently focused window among the visible ones.
+pub fn get_focused_window<B: GetVisible>(backend: &B) -> Result<Option<u64>> {
+    let windows = backend.get_visible()?;
+    Ok(windows.iter().find(|w| w.focused).map(|w| w.id))
+}
+
+/// Get workspace to switch to, in the order the window manager switches through them.
+/// If `wrap` is true, it will wrap around to the first/last workspace if no next/previous
+/// workspace is available.
+/// Returns `None` if there is no workspace to switch to.
+pub fn get_workspace_to_switch_to<B: GetWorkspaces>(backend: &B, direction: linear::Direction, wrap: bool) -> Result<Option<u64>> {
+    let sequence = get_workspace_sequence(backend)?;
+    let workspace_id = match sequence.next(direction) {
+        None if wrap => sequence.first(direction),
+        workspace_id => workspace_id,
+    };
+    if workspace_id.is_none() {
+        logging::info!("No workspaces available to switch to.");
+    }
+    Ok(workspace_id)
+}
+
+/// Get visible workspace to switch to based on the position of their outputs.
+/// If `wrap` is true, it will wrap around to the first workspace on the axis of the direction.
+/// Returns `None` if there is no workspace in the direction.
+pub fn get_workspace_in_direction<B: GetWorkspaces>(backend: &B, direction: planar::Direction, wrap: bool) -> Result<Option<u64>> {
+    let mut arrangement = get_workspace_arrangement(backend)?;
+    let workspace_id = match arrangement.next(direction) {
+        Some(workspace) => Some(workspace.id),
+        None if wrap => arrangement.first(direction).map(|w| w.id),
+        None => None,
+    };
+    if workspace_id.is_none() {
+        logging::info!("No workspaces available to switch to.");
+    }
+    Ok(workspace_id)
+}
+
+/// Get the workspace ID of a specific workspace number, like `workspace number` of i3 does, the
+/// first in switching order when several workspaces share it.
+/// If no workspace has the number, it will log a message and return `None`.
+pub fn get_workspace_of_number<B: GetWorkspaces>(backend: &B, number: usize) -> Result<Option<u64>> {
+    let workspaces = backend.get_workspaces()?;
+    let workspace_id = workspaces.iter().find(|w| w.num == Some(number)).map(|w| w.id);
+    if workspace_id.is_none() {
+        logging::info!("No workspace number: {}.", number);
+    }
+    Ok(workspace_id)
+}
+
+/// Get the scratchpad window to show, next to the focused one shown from the scratchpad.
+/// Without such a focused window, the first one in the direction is shown.
+/// If `wrap` is true, it will wrap around to the first/last scratchpad window.
+/// Returns `None` if there is no scratchpad window to show.
+pub fn get_scratchpad_window_to_switch_to<B: GetScratchpad>(backend: &B, options: &Options, direction: linear::Direction, wrap: bool) -> Result<Option<u64>> {
+    let windows = get_scratchpad_windows(backend, options)?;
+    let sequence = as_scratchpad_sequence(&windows);
+    let window_id = match sequence.next(direction) {
+        _ if !any_focused(&windows) => sequence.first(direction),
+        None if wrap => sequence.first(direction),
+        window_id => window_id,
+    };
+    if window_id.is_none() {
+        logging::info!("No scratchpad windows available to show.");
+    }
+    Ok(window_id)
+}
+
+/// Get the window ID of a specific scratchpad window number.
+/// If the number is out of bounds, it will log a message and return `None`.
+pub fn get_scratchpad_window_of_number<B: GetScratchpad>(backend: &B, options: &Options, number: usize) -> Result<Option<u64>> {
+    let sequence = as_scratchpad_sequence(&get_scratchpad_windows(backend, options)?);
     if number >= sequence.size() {
-        logging::critical!("Invalid window number: {}. There are only {} windows available.", number, sequence.size());
+        logging::info!("No scratchpad window number: {}. There are only {} windows available.", number, sequence.size());
+        return Ok(None);
     }
-    sequence[number]
+    Ok(Some(sequence[number]))
+}
+
+/// Get the focused window if it is shown from the scratchpad, it is hidden before the next one
+/// is shown.
+pub fn get_focused_scratchpad_window<B: GetScratchpad>(backend: &B) -> Result<Option<u64>> {
+    let windows = backend.get_scratchpad()?;
+    Ok(windows.iter().find(|w| w.focused).map(|w| w.id))
 }
 
 // ----------------------------------------------------------
@@ -67,45 +337,139 @@ pub fn get_window_of_number<B: GetVisible + GetTabs>(backend: &B, number: usize)
 // ----------------------------------------------------------
 
 /// Get the linear sequence of windows based on the i3 tree structure.
-/// If there are focused floating windows, it will return the sequence of those windows.
-/// Otherwise, it will return the sequence of available tabs in the current workspace.
-fn get_linear_sequence<B: GetVisible + GetTabs>(backend: &B) -> linear::Sequence {
-    let windows = backend.get_visible()
-        .expect("Failed to get visible windows from backend");
+fn get_linear_sequence<B: GetVisible + GetTabs>(backend: &B, options: &Options) -> Result<linear::Sequence> {
+    Ok(as_sequence(&get_linear_windows(backend, options)?))
+}
+
+/// Get the windows navigated linearly, in their order.
+/// If there are focused floating windows and they are preferred, it will return those windows,
+/// from left to right. Otherwise, it will return the available tabs in the current workspace.
+pub fn get_linear_windows<B: GetVisible + GetTabs>(backend: &B, options: &Options) -> Result<Windows> {
+    let windows = navigable(backend.get_visible()?, options);
     let mut floating = floating(&windows);
 
     logging::debug!("Floating windows: {:?}", floating);
 
-    if any_focused(&floating) {
+    if options.linear == Linear::Floating && any_focused(&floating) {
         logging::debug!("Using floating windows for linear sequence.");
         floating.sort_by_key(|w| w.rect.x);
-        as_sequence(&floating)
+        Ok(floating)
     } else {
         logging::debug!("Using available tabs for linear sequence.");
-        let windows = backend.get_tabs()
-            .expect("Failed to get tabs from backend");
-        as_sequence(&windows)
+        Ok(navigable(backend.get_tabs_at(options.tabs)?, options))
     }
 }
 
-/// Get the planar arrangement of windows based on the i3 tree structure.
-/// If there are focused floating windows, it will return the arrangement of those windows.
-/// Otherwise, it will return the arrangement of visible windows in the current workspace.
-fn get_planar_arrangement<B: GetVisible>(backend: &B) -> planar::Arrangement {
-    let windows = backend.get_visible()
-        .expect("Failed to get visible windows from backend");
+/// Get the planar layout of windows based on the i3 tree structure.
+/// If there are focused floating windows, it will return the layout of those windows.
+/// Otherwise, it will return the layout of visible windows in the current workspace.
+/// If `group` is true, tabbed and stacked containers are arranged as a single window.
+/// While a fullscreen window is focused, the layout is limited by the fullscreen policy.
+pub fn get_planar_layout<B: GetVisible + GetGroups + GetOutputs>(backend: &B, options: &Options, group: bool) -> Result<planar::Layout> {
+    let windows = navigable(if group { backend.get_groups() } else { backend.get_visible() }?, options);
+    let windows = uncovered(windows, options.fullscreen);
     let floating = floating(&windows);
+    let outputs = get_output_rects(backend);
 
     if any_focused(&floating) {
-        logging::debug!("Using floating windows for planar arrangement.");
-        return as_arrangement(floating, planar::Relation::Center);
+        logging::debug!("Using floating windows for planar layout.");
+        Ok(as_layout(outputs, floating, options.floating))
     } else {
-        logging::debug!("Using available tiled for planar arrangement.");
+        logging::debug!("Using available tiled for planar layout.");
         let tiled = tiled(&windows);
-        return as_arrangement(tiled, planar::Relation::Border);
+        Ok(as_layout(outputs, tiled, options.tiled))
     }
 }
 
+/// Get the scratchpad windows in the order they are cycled through.
+/// They are ordered by their IDs, as the window manager reorders them when they are shown and
+/// hidden.
+pub fn get_scratchpad_windows<B: GetScratchpad>(backend: &B, options: &Options) -> Result<Windows> {
+    let mut windows = navigable(backend.get_scratchpad()?, options);
+    windows.sort_by_key(|w| w.id);
+    Ok(windows)
+}
+
+/// Get the rectangles of active outputs, or none when the backend doesn't know them, in which
+/// case all windows are navigated as one plane.
+fn get_output_rects<B: GetOutputs>(backend: &B) -> Vec<Rect> {
+    match backend.get_outputs() {
+        Ok(outputs) => outputs.iter().map(|o| o.rect).collect(),
+        Err(e) => {
+            logging::debug!("Navigating without outputs: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Get the sequence of all workspaces, positioned at the focused one.
+pub fn get_workspace_sequence<B: GetWorkspaces>(backend: &B) -> Result<linear::Sequence> {
+    let workspaces = backend.get_workspaces()?;
+    let focused = workspaces.iter().position(|w| w.focused).unwrap_or(0);
+    Ok(linear::Sequence::new(workspaces.iter().map(|w| w.id).collect(), focused))
+}
+
+/// Get the planar arrangement of visible workspaces, each covering its output.
+fn get_workspace_arrangement<B: GetWorkspaces>(backend: &B) -> Result<planar::Arrangement> {
+    let workspaces = backend.get_workspaces()?;
+    let windows: Windows = workspaces.iter()
+        .filter(|w| w.visible)
+        .map(|w| Window { id: w.id, rect: w.rect, focused: w.focused, ..Default::default() })
+        .collect();
+    Ok(as_arrangement(windows, planar::Relation::Border))
+}
+
+/// Returns the first of the matching windows, or the one after the focused one, so that
+/// repeating the same lookup cycles through all of them.
+fn next_match(matches: &[&Window]) -> Option<u64> {
+    let focused = matches.iter().position(|w| w.focused);
+    let sequence = linear::Sequence::new(matches.iter().map(|w| w.id).collect(), focused.unwrap_or(0));
+    match focused {
+        Some(_) => sequence.next(linear::Direction::Next).or(sequence.first(linear::Direction::Next)),
+        None => sequence.first(linear::Direction::Next),
+    }
+}
+
+/// Forgets closed and excluded windows, so that the history doesn't lead to them.
+fn prune_history<B: GetAll>(backend: &B, options: &Options, history: &mut History) {
+    match backend.get_all() {
+        Ok(windows) => history.prune(&navigable(windows, options).iter().map(|w| w.id).collect::<Vec<u64>>()),
+        Err(e) => {
+            logging::warning!("Failed to get windows for pruning focus history: {}", e);
+        }
+    }
+}
+
+/// Returns the windows that can be switched to, and the focused one, which navigation starts
+/// from even when it is excluded.
+fn navigable(windows: Windows, options: &Options) -> Windows {
+    windows.into_iter()
+        .filter(|w| w.focused || options.is_navigable(w))
+        .collect()
+}
+
+/// Returns the windows that are not covered by a fullscreen window. Backends without a notion of
+/// visibility return the windows behind one, they are recognized by lying within it.
+/// While a fullscreen window is focused, only the windows the policy leads to are kept.
+fn uncovered(windows: Windows, policy: Fullscreen) -> Windows {
+    let fullscreen: Vec<Rect> = windows.iter().filter(|w| w.fullscreen).map(|w| w.rect).collect();
+    let in_fullscreen = windows.iter().any(|w| w.focused && w.fullscreen);
+    windows.into_iter()
+        .filter(|w| match policy {
+            Fullscreen::Stay if in_fullscreen => w.focused,
+            Fullscreen::Outputs if in_fullscreen => w.fullscreen,
+            Fullscreen::Leave if in_fullscreen => true,
+            _ => w.focused || w.fullscreen || !fullscreen.iter().any(|r| contains(r, &w.rect)),
+        })
+        .collect()
+}
+
+/// Returns whether the inner rectangle lies within the outer one.
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.left() >= outer.left() && inner.right() <= outer.right() &&
+        inner.top() >= outer.top() && inner.bottom() <= outer.bottom()
+}
+
 /// Returns a collection of windows that are floating, i.e., those that are not tiled.
 fn floating(windows: &Windows) -> Windows {
     windows.iter()
@@ -122,19 +486,31 @@ fn tiled(windows: &Windows) -> Windows {
         .collect()
 }
 
-/// Converts a JSON node to a `planar::Arrangement`.
-/// This function assumes that the node represents a workspace or root node
-/// and contains a list of windows.
+/// Converts a collection of `Windows` to a `planar::Arrangement`, positioned at the focused
+/// window.
 fn as_arrangement(windows: Windows, relation: planar::Relation) -> planar::Arrangement {
     let current = focused_index(&windows).unwrap_or(0);
     planar::Arrangement::new(windows, Some(current), Some(relation))
 }
 
+/// Converts windows on the outputs to a `planar::Layout`, positioned at the focused window.
+fn as_layout(outputs: Vec<Rect>, windows: Windows, relation: planar::Relation) -> planar::Layout {
+    let current = focused_index(&windows).unwrap_or(0);
+    planar::Layout::new(outputs, windows, Some(current), Some(relation))
+}
+
 /// Converts a collection of `Windows` to a `linear::Sequence`.
 /// This function creates a sequence of window IDs and marks the focused window by its index.
 fn as_sequence(windows: &Windows) -> linear::Sequence {
     let focused = focused_index(windows).unwrap_or(0);
-    return linear::Sequence::new(windows.iter().map(|w| w.id).collect(), focused);
+    linear::Sequence::new(windows.iter().map(|w| w.id).collect(), focused)
+}
+
+/// Converts scratchpad windows to a `linear::Sequence`, positioned at the focused one, if any.
+/// Scratchpad windows are usually hidden, none of them being focused is not worth a warning.
+fn as_scratchpad_sequence(windows: &Windows) -> linear::Sequence {
+    let focused = windows.iter().position(|w| w.focused).unwrap_or(0);
+    linear::Sequence::new(windows.iter().map(|w| w.id).collect(), focused)
 }
 
 /// Returns whether any window in the provided `Windows` is focused.
@@ -154,16 +530,16 @@ fn focused_index(windows: &Windows) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
-    use crate::types::Rect;
-    use crate::types::Window;
     use super::*;
+    use crate::error::Error;
+    use crate::types::Properties;
 
     /// Tests for floating and tiled windows.
     #[test]
     fn test_floating_and_tiled() {
         let windows = vec![
-            Window { id: 1, rect: Rect {x: 0, y: 0, w: 100, h: 100}, focused: true, floating: false },
-            Window { id: 2, rect: Rect {x: 100, y: 100, w: 200, h: 200}, focused: false, floating: true },
+            Window { id: 1, rect: Rect {x: 0, y: 0, w: 100, h: 100}, focused: true, ..Default::default() },
+            Window { id: 2, rect: Rect {x: 100, y: 100, w: 200, h: 200}, floating: true, ..Default::default() },
         ];
         let floating_windows = floating(&windows);
         let tiled_windows = tiled(&windows);
@@ -175,22 +551,22 @@ mod tests {
     #[test]
     fn test_any_focused() {
         let windows = vec![
-            Window { id: 1, rect: Rect {x: 0, y: 0, w: 100, h: 100}, focused: true, floating: false },
-            Window { id: 2, rect: Rect {x: 100, y: 100, w: 200, h: 200}, focused: false, floating: true },
+            Window { id: 1, rect: Rect {x: 0, y: 0, w: 100, h: 100}, focused: true, ..Default::default() },
+            Window { id: 2, rect: Rect {x: 100, y: 100, w: 200, h: 200}, floating: true, ..Default::default() },
         ];
         assert!(any_focused(&windows));
     }
 
     /// Tests for visible nodes extraction.
     #[test]
-    fn test_as_arrangement() {
+    fn test_as_layout() {
         let windows = vec![
-            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: true, floating: false },
-            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, focused: false, floating: true },
+            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, ..Default::default() },
+            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, focused: true, floating: true, ..Default::default() },
         ];
-        let arrangement = as_arrangement(windows, planar::Relation::Border);
-        assert_eq!(arrangement.windows.len(), 2);
-        assert_eq!(arrangement.current, 0);
+        let layout = as_layout(vec![], windows, planar::Relation::Border);
+        assert_eq!(layout.windows.len(), 2);
+        assert_eq!(layout.current, 1);
     }
 
     /// Tests for focused index extraction.
@@ -198,14 +574,14 @@ mod tests {
     #[test]
     fn test_focused_index() {
         let windows = vec![
-            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: true, floating: false },
-            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, focused: false, floating: true },
+            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: true, ..Default::default() },
+            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, floating: true, ..Default::default() },
         ];
         assert_eq!(focused_index(&windows), Some(0));
 
         let windows = vec![
-            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: false, floating: false },
-            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, focused: false, floating: true },
+            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, ..Default::default() },
+            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, floating: true, ..Default::default() },
         ];
         assert_eq!(focused_index(&windows), None);
     }
@@ -216,11 +592,295 @@ mod tests {
     #[test]
     fn test_as_sequence() {
         let windows = vec![
-            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: true, floating: false },
-            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, focused: false, floating: true },
+            Window { id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused: true, ..Default::default() },
+            Window { id: 2, rect: Rect { x: 100, y: 100, w: 200, h: 200 }, floating: true, ..Default::default() },
         ];
         let sequence = as_sequence(&windows);
         assert_eq!(sequence[0], 1);
         assert_eq!(sequence[1], 2);
     }
+
+    struct Workspaces(Vec<crate::types::Workspace>);
+
+    impl GetWorkspaces for Workspaces {
+        fn get_workspaces(&self) -> crate::error::Result<crate::types::Workspaces> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Tests workspace switching.
+    /// We expect all workspaces to be switched through in order, but only the visible ones to
+    /// be found in directions.
+    #[test]
+    fn test_workspaces() {
+        let workspace = |id: u64, x: i32, focused: bool, visible: bool| crate::types::Workspace {
+            id, num: Some(id as usize), name: id.to_string(), rect: Rect { x, y: 0, w: 100, h: 100 }, focused, visible,
+        };
+        let backend = Workspaces(vec![
+            workspace(1, 0, false, false),
+            workspace(2, 0, true, true),
+            workspace(3, 100, false, false),
+            workspace(4, 100, false, true),
+            crate::types::Workspace { num: None, name: "mail".to_string(), ..workspace(5, 200, false, false) },
+        ]);
+        assert_eq!(get_workspace_to_switch_to(&backend, linear::Direction::Next, false), Ok(Some(3)));
+        assert_eq!(get_workspace_to_switch_to(&backend, linear::Direction::Prev, false), Ok(Some(1)));
+        assert_eq!(get_workspace_in_direction(&backend, planar::Direction::Right, false), Ok(Some(4)));
+        assert_eq!(get_workspace_in_direction(&backend, planar::Direction::Left, false), Ok(None));
+        assert_eq!(get_workspace_in_direction(&backend, planar::Direction::Left, true), Ok(Some(4)));
+        assert_eq!(get_workspace_of_number(&backend, 3), Ok(Some(3)));
+        assert_eq!(get_workspace_of_number(&backend, 5), Ok(None));
+        assert_eq!(get_workspace_of_number(&backend, 0), Ok(None));
+    }
+
+    struct Scratchpad(Windows);
+
+    impl GetScratchpad for Scratchpad {
+        fn get_scratchpad(&self) -> Result<Windows> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Tests scratchpad cycling.
+    /// We expect scratchpad windows to be cycled through in the order of their IDs, from the
+    /// focused one, or from the first one when none is shown and focused.
+    #[test]
+    fn test_scratchpad() {
+        let window = |id: u64, focused: bool| Window {
+            id, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused, floating: true, ..Default::default()
+        };
+        let options = Options::default();
+        let backend = Scratchpad(vec![window(34, false), window(36, true), window(32, false)]);
+        assert_eq!(get_focused_scratchpad_window(&backend), Ok(Some(36)));
+        assert_eq!(get_scratchpad_window_to_switch_to(&backend, &options, linear::Direction::Next, false), Ok(None));
+        assert_eq!(get_scratchpad_window_to_switch_to(&backend, &options, linear::Direction::Next, true), Ok(Some(32)));
+        assert_eq!(get_scratchpad_window_to_switch_to(&backend, &options, linear::Direction::Prev, false), Ok(Some(34)));
+        assert_eq!(get_scratchpad_window_of_number(&backend, &options, 0), Ok(Some(32)));
+        assert_eq!(get_scratchpad_window_of_number(&backend, &options, 3), Ok(None));
+
+        let backend = Scratchpad(vec![window(34, false), window(32, false)]);
+        assert_eq!(get_focused_scratchpad_window(&backend), Ok(None));
+        assert_eq!(get_scratchpad_window_to_switch_to(&backend, &options, linear::Direction::Next, false), Ok(Some(32)));
+        assert_eq!(get_scratchpad_window_to_switch_to(&backend, &options, linear::Direction::Prev, false), Ok(Some(34)));
+    }
+
+    struct NoWorkspaces;
+
+    impl GetWorkspaces for NoWorkspaces {
+        fn get_workspaces(&self) -> crate::error::Result<crate::types::Workspaces> {
+            Err(Error::Unsupported("Workspaces not supported in this backend".to_string()))
+        }
+    }
+
+    /// Tests that failures of the backend are told apart from finding nothing.
+    #[test]
+    fn test_backend_errors() {
+        let unsupported = Err(Error::Unsupported("Workspaces not supported in this backend".to_string()));
+        assert_eq!(get_workspace_to_switch_to(&NoWorkspaces, linear::Direction::Next, true), unsupported);
+        assert_eq!(get_workspace_in_direction(&NoWorkspaces, planar::Direction::Left, true), unsupported);
+        assert_eq!(get_workspace_of_number(&NoWorkspaces, 0), unsupported);
+    }
+
+    struct AllWindows(Windows);
+
+    impl GetAll for AllWindows {
+        fn get_all(&self) -> Result<Windows> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Tests window lookup by properties.
+    /// We expect the best match first, and the following matches when repeated from a match.
+    #[test]
+    fn test_window_matching() {
+        let window = |id: u64, focused: bool, class: &str, title: &str| Window {
+            id, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, focused,
+            properties: Properties {
+                class: Some(class.to_string()),
+                title: Some(title.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut backend = AllWindows(vec![
+            window(1, true, "firefox", "Docs"),
+            window(2, false, "Alacritty", "vim"),
+            window(3, false, "Emacs", "scratch"),
+            window(4, false, "Alacritty", "fish"),
+        ]);
+        let options = Options::default();
+        assert_eq!(get_window_matching(&backend, &options, "alacritty"), Ok(Some(2)));
+        assert_eq!(get_window_matching(&backend, &options, "vim"), Ok(Some(2)));
+        assert_eq!(get_window_matching(&backend, &options, "chrome"), Ok(None));
+
+        backend.0[0].focused = false;
+        backend.0[1].focused = true;
+        assert_eq!(get_window_matching(&backend, &options, "alacritty"), Ok(Some(4)));
+        backend.0[1].focused = false;
+        backend.0[3].focused = true;
+        assert_eq!(get_window_matching(&backend, &options, "alacritty"), Ok(Some(2)));
+
+        let criteria = Criteria { class: Some("alacritty".to_string()), ..Default::default() };
+        assert_eq!(get_window_to_raise(&backend, &options, &criteria), Ok(Some(2)));
+        backend.0[3].focused = false;
+        assert_eq!(get_window_to_raise(&backend, &options, &criteria), Ok(Some(2)));
+        let criteria = Criteria { class: Some("alacrit".to_string()), ..Default::default() };
+        assert_eq!(get_window_to_raise(&backend, &options, &criteria), Ok(None));
+    }
+
+    /// Tests urgent window lookup.
+    /// We expect the window urgent for the longest time first, then the following ones when
+    /// focus stays on an urgent window.
+    #[test]
+    fn test_urgent_window() {
+        let window = |id: u64, urgent: bool| Window {
+            id, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, urgent, ..Default::default()
+        };
+        let mut backend = AllWindows(vec![window(1, false), window(2, true), window(3, true)]);
+        let mut urgency = Urgency::new(vec![3]);
+        let options = Options::default();
+        assert_eq!(get_urgent_window(&backend, &options, &mut urgency), Ok(Some(3)));
+        assert_eq!(urgency.windows(), &[3, 2]);
+
+        backend.0[2].focused = true;
+        assert_eq!(get_urgent_window(&backend, &options, &mut urgency), Ok(Some(2)));
+
+        backend.0[1].urgent = false;
+        backend.0[2].urgent = false;
+        assert_eq!(get_urgent_window(&backend, &options, &mut urgency), Ok(None));
+        assert!(urgency.windows().is_empty());
+    }
+
+    struct Screen {
+        visible: Windows,
+        tabs: Windows,
+    }
+
+    impl GetVisible for Screen {
+        fn get_visible(&self) -> Result<Windows> {
+            Ok(self.visible.clone())
+        }
+    }
+
+    impl GetTabs for Screen {
+        fn get_tabs(&self) -> Result<Windows> {
+            Ok(self.tabs.clone())
+        }
+    }
+
+    impl GetGroups for Screen {
+        fn get_groups(&self) -> Result<Windows> {
+            self.get_visible()
+        }
+    }
+
+    impl GetOutputs for Screen {
+        fn get_outputs(&self) -> Result<crate::types::Outputs> {
+            Err(Error::Unsupported("Outputs not supported in this backend".to_string()))
+        }
+    }
+
+    /// Tests navigation options.
+    /// We expect the tabs instead of the focused floating windows when tabs are preferred, and
+    /// the relation of the navigated windows to be the configured one.
+    #[test]
+    fn test_options() {
+        let window = |id: u64, x: i32, focused: bool, floating: bool| Window {
+            id, rect: Rect { x, y: 0, w: 100, h: 100 }, focused, floating, ..Default::default()
+        };
+        let backend = Screen {
+            visible: vec![window(1, 0, false, false), window(2, 300, true, true), window(3, 100, false, true)],
+            tabs: vec![window(1, 0, false, false), window(4, 0, false, false)],
+        };
+        let mut options = Options::default();
+        assert_eq!(get_window_of_number(&backend, &options, 0), Ok(Some(3)));
+        assert_eq!(get_planar_layout(&backend, &options, false).map(|l| l.relation), Ok(planar::Relation::Center));
+
+        options.linear = Linear::Tabs;
+        options.floating = planar::Relation::Border;
+        assert_eq!(get_window_of_number(&backend, &options, 0), Ok(Some(1)));
+        assert_eq!(get_window_to_switch_to(&backend, &options, linear::Direction::Next, false), Ok(Some(4)));
+        assert_eq!(get_planar_layout(&backend, &options, false).map(|l| l.relation), Ok(planar::Relation::Border));
+    }
+
+    /// Tests fullscreen policies.
+    /// We expect windows covered by a fullscreen window to be skipped, and directions from a
+    /// focused fullscreen window to lead where the policy says.
+    #[test]
+    fn test_fullscreen() {
+        let window = |id: u64, x: i32, w: i32, focused: bool, fullscreen: bool| Window {
+            id, rect: Rect { x, y: 0, w, h: 100 }, focused, fullscreen, ..Default::default()
+        };
+        let backend = Screen {
+            visible: vec![
+                window(1, 0, 200, true, true),
+                window(2, 0, 100, false, false),
+                window(3, 200, 100, false, false),
+                window(4, 300, 200, false, true),
+            ],
+            tabs: vec![],
+        };
+        let mut options = Options::default();
+        assert_eq!(get_fullscreen_window(&backend), Ok(Some(1)));
+        assert_eq!(get_window_in_direction(&backend, &options, planar::Direction::Right, None, false), Ok(None));
+
+        options.fullscreen = Fullscreen::Outputs;
+        assert_eq!(get_window_in_direction(&backend, &options, planar::Direction::Right, None, false), Ok(Some(4)));
+
+        options.fullscreen = Fullscreen::Leave;
+        assert_eq!(get_window_in_direction(&backend, &options, planar::Direction::Right, None, false), Ok(Some(3)));
+
+        let backend = Screen {
+            visible: vec![
+                window(1, 0, 200, false, true),
+                window(2, 0, 100, false, false),
+                window(3, 200, 100, true, false),
+            ],
+            tabs: vec![],
+        };
+        assert_eq!(get_fullscreen_window(&backend), Ok(None));
+        let layout = get_planar_layout(&backend, &options, false).unwrap();
+        assert_eq!(layout.windows.iter().map(|w| w.id).collect::<Vec<u64>>(), vec![1, 3]);
+    }
+
+    /// Tests exclusion rules.
+    /// We expect excluded windows to be skipped unless included again, except for the focused
+    /// window navigation starts from.
+    #[test]
+    fn test_rules() {
+        let window = |id: u64, x: i32, focused: bool, floating: bool, class: &str| Window {
+            id, rect: Rect { x, y: 0, w: 100, h: 100 }, focused, floating,
+            properties: Properties { class: Some(class.to_string()), ..Default::default() },
+            ..Default::default()
+        };
+        let backend = Screen {
+            visible: vec![
+                window(1, 0, true, false, "Alacritty"),
+                window(2, 100, false, false, "Conky"),
+                window(3, 200, false, false, "Alacritty"),
+            ],
+            tabs: vec![],
+        };
+        let options = Options {
+            exclude: vec![Criteria::parse("class=conky").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(get_window_in_direction(&backend, &options, planar::Direction::Right, None, false), Ok(Some(3)));
+
+        let options = Options {
+            exclude: vec![Criteria::parse("floating=false").unwrap()],
+            include: vec![Criteria::parse("class=Conky").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(get_window_in_direction(&backend, &options, planar::Direction::Right, None, false), Ok(Some(2)));
+
+        let backend = AllWindows(backend.visible);
+        let options = Options {
+            exclude: vec![Criteria::parse("class=alacritty").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(get_window_matching(&backend, &options, "alacritty"), Ok(None));
+        assert!(options.is_navigable(&backend.0[1]));
+        assert!(!options.is_navigable(&backend.0[0]));
+    }
 }
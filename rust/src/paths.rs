@@ -0,0 +1,83 @@
+//! Locations of the files shared between invocations.
+
+use std::fs::{self, DirBuilder};
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// Returns the directory of the files that should live only as long as the user session.
+/// Without `XDG_RUNTIME_DIR`, it's a directory of the user in the temporary directory, which
+/// other users could otherwise read from or plant files in.
+pub fn runtime_dir() -> io::Result<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => {
+            let user = std::env::var("USER").unwrap_or_default();
+            let dir = std::env::temp_dir().join(format!("i3switch-{}", user));
+            private_dir(&dir)?;
+            Ok(dir)
+        }
+    }
+}
+
+/// Creates a directory only its owner can access, or checks that the existing one is such.
+/// A directory of another user passes too, its files are out of reach anyway.
+fn private_dir(dir: &Path) -> io::Result<()> {
+    match DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    let metadata = fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied,
+            format!("{} is not a private directory", dir.display())));
+    }
+    Ok(())
+}
+
+/// Returns the path of the configuration file to use, the one of the user before the system
+/// wide one, or `None` when there is none.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    let home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    let system = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or("/etc/xdg".to_string());
+
+    home.into_iter()
+        .chain(system.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("i3switch").join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests for the directory of the runtime files without XDG_RUNTIME_DIR.
+    /// We expect it to be created private, and shared or linked directories to be refused.
+    #[test]
+    fn test_private_dir() {
+        let base = std::env::temp_dir().join(format!("i3switch-test-paths-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir(&base).expect("Failed to create test directory");
+
+        let dir = base.join("private");
+        assert!(private_dir(&dir).is_ok());
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        assert!(private_dir(&dir).is_ok());
+
+        let shared = base.join("shared");
+        DirBuilder::new().mode(0o755).create(&shared).unwrap();
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(private_dir(&shared).is_err());
+
+        let link = base.join("link");
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(private_dir(&link).is_err());
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -1,22 +1,22 @@
-/// This module provides functions to align rectangles in a 2D plane based on their properties and
-/// specified directions.
-///
-/// ```
-/// use i3switch::planar::alignment::{get_properties, next_in_direction, Direction, Relation};
-/// use i3switch::types::rect::Rect;
-///
-/// let rects = vec![
-///    Rect { x: 0, y: 0, w: 10, h: 10 },
-///    Rect { x: 20, y: 0, w: 10, h: 10 },
-///    Rect { x: 0, y: 20, w: 10, h: 10 },
-///    Rect { x: 20, y: 20, w: 10, h: 10 },
-///    Rect { x: 10, y: 10, w: 10, h: 10 },
-/// ];
-/// let rect_refs: Vec<&Rect> = rects.iter().collect();
-/// let properties = get_properties(Relation::Border, Direction::Right);
-/// let next = next_in_direction(&rect_refs, &rect_refs[0], &properties);
-/// assert_eq!(next, Some(4));
-/// ```
+//! This module provides functions to align rectangles in a 2D plane based on their properties and
+//! specified directions.
+//!
+//! ```
+//! use i3switch::planar::alignment::{get_properties, next_in_direction, Direction, Relation};
+//! use i3switch::types::rect::Rect;
+//!
+//! let rects = vec![
+//!    Rect { x: 0, y: 0, w: 10, h: 10 },
+//!    Rect { x: 20, y: 0, w: 10, h: 10 },
+//!    Rect { x: 0, y: 20, w: 10, h: 10 },
+//!    Rect { x: 20, y: 20, w: 10, h: 10 },
+//!    Rect { x: 10, y: 10, w: 10, h: 10 },
+//! ];
+//! let rect_refs: Vec<&Rect> = rects.iter().collect();
+//! let properties = get_properties(Relation::Border, Direction::Right);
+//! let next = next_in_direction(&rect_refs, &rect_refs[0], &properties);
+//! assert_eq!(next, Some(4));
+//! ```
 
 use crate::types::Rect;
 use crate::logging;
@@ -99,12 +99,11 @@ pub fn get_properties(relation: Relation, direction: Direction) -> Properties {
 /// Finds the closest rectangle in a given direction based on the properties.
 fn closest_in_direction<'a>(rects: &'a [&Rect], at_least: i32, properties: &Properties) -> Vec<&'a Rect> {
     // Find the rectangle that is closest to the specified near extent.
-    let min_key: for<'b> fn(&'b i32) -> i32;
-    if (properties.comp)(i32::MIN, i32::MAX) {
-        min_key = |extent: &i32| -extent
+    let min_key: for<'b> fn(&'b i32) -> i32 = if (properties.comp)(i32::MIN, i32::MAX) {
+        |extent: &i32| -extent
     } else {
-        min_key = |extent: &i32| *extent
-    }
+        |extent: &i32| *extent
+    };
     // Filter the rectangles that match the near extent condition.
     let min_extent = rects.iter()
         .filter(|rect| (properties.comp)((properties.near)(rect), at_least))
@@ -115,7 +114,7 @@ fn closest_in_direction<'a>(rects: &'a [&Rect], at_least: i32, properties: &Prop
     match min_extent {
         Some(min) => rects.iter()
             .filter(|rect| (properties.near)(rect) == min)
-            .map(|rect| *rect)
+            .copied()
             .collect(),
         None => vec![],
     }
@@ -132,7 +131,7 @@ fn aligned_in_direction<'a>(rects: &'a [&Rect], close_to: i32, properties: &Prop
     match min_distance {
         Some(min) => rects.iter()
             .filter(|rect| ((properties.axis)(rect) - close_to).abs() == min)
-            .map(|rect| *rect)
+            .copied()
             .collect(),
         None => vec![],
     }
@@ -140,21 +139,21 @@ fn aligned_in_direction<'a>(rects: &'a [&Rect], close_to: i32, properties: &Prop
 
 /// Finds the next rectangle in a given direction based on the properties.
 #[allow(dead_code)]
-pub fn next_in_direction<'a>(rects: &'a [&Rect], current: &Rect, properties: &Properties) -> Option<usize> {
-    let at_least = (properties.far)(&current);
-    let mut closest = closest_in_direction(rects, at_least, &properties);
+pub fn next_in_direction(rects: &[&Rect], current: &Rect, properties: &Properties) -> Option<usize> {
+    let at_least = (properties.far)(current);
+    let mut closest = closest_in_direction(rects, at_least, properties);
     logging::debug!("Closest found: {:?} for extent: {}", closest.len(), at_least);
 
     if closest.iter().any(|rect| std::ptr::eq(*rect, current)) {
         logging::debug!("Current rectangle is in the closest set, looking for next.");
 
         let at_least = if (properties.comp)(i32::MIN, i32::MAX) { at_least - 1 } else { at_least + 1 };
-        closest = closest_in_direction(rects, at_least, &properties);
+        closest = closest_in_direction(rects, at_least, properties);
         logging::debug!("Closest after safety margin: {:?} for extent: {}", closest.len(), at_least);
     }
 
-    let axis = (properties.axis)(&current);
-    let aligned = aligned_in_direction(&closest, axis, &properties);
+    let axis = (properties.axis)(current);
+    let aligned = aligned_in_direction(&closest, axis, properties);
     logging::debug!("Aligned found: {:?} for axis: {}", aligned.len(), axis);
 
     if let Some(next) = aligned.first() {
@@ -165,13 +164,13 @@ pub fn next_in_direction<'a>(rects: &'a [&Rect], current: &Rect, properties: &Pr
 }
 
 #[allow(dead_code)]
-pub fn first_of_direction<'a>(rects: &'a [&Rect], current: &Rect, properties: &Properties) -> Option<usize> {
+pub fn first_of_direction(rects: &[&Rect], current: &Rect, properties: &Properties) -> Option<usize> {
     let at_least: i32 = if (properties.comp)(i32::MIN, i32::MAX) { i32::MAX } else { i32::MIN };
     let closest = closest_in_direction(rects, at_least, properties);
     logging::debug!("Closest found: {:?} for extent: {}", closest.len(), at_least);
 
-    let axis = (properties.axis)(&current);
-    let aligned = aligned_in_direction(&closest, axis, &properties);
+    let axis = (properties.axis)(current);
+    let aligned = aligned_in_direction(&closest, axis, properties);
     logging::debug!("Aligned found: {:?} for axis: {}", aligned.len(), axis);
 
     if let Some(first) = aligned.first() {
@@ -260,7 +259,7 @@ mod tests {
                 }
             };
         }
-        let rects = vec![
+        let rects = [
             Rect { x:  0, y:  0, w: 10, h: 10 },
             Rect { x: 20, y:  0, w: 10, h: 10 },
             Rect { x:  0, y: 20, w: 10, h: 10 },
@@ -313,7 +312,7 @@ mod tests {
                 }
             };
         }
-        let rects = vec![
+        let rects = [
             Rect { x:  0, y:  0, w: 10, h: 10 }, // Center at ( 5,  5)
             Rect { x: 20, y:  0, w: 10, h: 10 }, // Center at (25,  5)
             Rect { x:  0, y: 20, w: 10, h: 10 }, // Center at ( 5, 25)
@@ -364,7 +363,7 @@ mod tests {
                     $relation, $direction, $current);
             };
         }
-        let rects = vec![
+        let rects = [
             Rect { x:  0, y:  0, w: 10, h: 10 }, // Center at ( 5,  5)
             Rect { x: 20, y:  0, w: 10, h: 10 }, // Center at (25,  5)
             Rect { x:  0, y: 20, w: 10, h: 10 }, // Center at ( 5, 25)
@@ -430,7 +429,7 @@ mod tests {
                     $relation, $direction, $current);
             };
         }
-        let rects = vec![
+        let rects = [
             Rect { x:  0, y:  0, w: 10, h: 10 }, // Center at ( 5,  5)
             Rect { x: 20, y:  0, w: 10, h: 10 }, // Center at (25,  5)
             Rect { x:  0, y: 20, w: 10, h: 10 }, // Center at ( 5, 25)
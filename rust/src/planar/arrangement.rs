@@ -27,15 +27,15 @@ impl Arrangement {
     pub fn next(&mut self, direction: Direction) -> Option<&Window> {
         let properties = alignment::get_properties(self.relation, direction);
         let rects: Vec<&Rect> = self.windows.iter().map(|w| &w.rect).collect();
-        let next_index = alignment::next_in_direction(&rects, rects[self.current], &properties);
-        return Some(&self.windows[next_index?]);
+        let next_index = alignment::next_in_direction(&rects, rects.get(self.current)?, &properties);
+        Some(&self.windows[next_index?])
     }
 
     /// Returns the first window on the axis of the specified direction, if it exists.
     pub fn first(&self, direction: Direction) -> Option<&Window> {
         let properties = alignment::get_properties(self.relation, direction);
         let rects: Vec<&Rect> = self.windows.iter().map(|w| &w.rect).collect();
-        let first_index = alignment::first_of_direction(&rects, rects[self.current], &properties);
-        return Some(&self.windows[first_index?]);
+        let first_index = alignment::first_of_direction(&rects, rects.get(self.current)?, &properties);
+        Some(&self.windows[first_index?])
     }
 }
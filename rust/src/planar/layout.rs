@@ -0,0 +1,201 @@
+//! Windows spread over multiple outputs, navigated output by output.
+//!
+//! Aligning all the windows in one plane gets confused by monitor edges, gaps between monitors
+//! and monitors of different sizes. The layout searches the output of the current window first,
+//! then continues to the neighbouring output in the direction, entering it from the side facing
+//! the current window.
+
+use crate::types::Rect;
+use crate::types::Window;
+use crate::planar::Direction;
+use crate::planar::Relation;
+use crate::planar::Arrangement;
+use crate::planar::alignment;
+
+/// This enum is used to specify where to wrap around when there is no window in the direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    /// Wrap around to the other edge of the current output.
+    Output,
+    /// Wrap around to the output on the other end of the layout.
+    Global,
+}
+
+pub struct Layout {
+    pub outputs:  Vec<Rect>,
+    pub windows:  Vec<Window>,
+    pub relation: Relation,
+    pub current:  usize,
+}
+
+impl Layout {
+    pub fn new(outputs: Vec<Rect>, windows: Vec<Window>, current: Option<usize>, relation: Option<Relation>) -> Self {
+        let relation = relation.unwrap_or(Relation::Border);
+        let current = current.unwrap_or(0);
+        // Ensure current index is within bounds
+        let current = if current < windows.len() { current } else { 0 };
+        Layout {
+            outputs,
+            windows,
+            relation,
+            current,
+        }
+    }
+
+    /// Returns the next window in the specified direction, if it exists.
+    /// When there is none, wraps around as specified by `wrap`.
+    pub fn next(&self, direction: Direction, wrap: Option<Wrap>) -> Option<&Window> {
+        let current = self.windows.get(self.current)?;
+        let Some(output) = self.output_of(&current.rect) else {
+            // Without knowing the outputs, everything is one plane
+            return self.next_in_plane(direction, wrap);
+        };
+
+        let mut local = self.arrangement_of(output);
+        if let Some(window) = local.next(direction) {
+            return self.find(window.id);
+        }
+
+        let properties = alignment::get_properties(Relation::Border, direction);
+        let occupied = self.occupied_outputs();
+        let rects: Vec<&Rect> = occupied.iter().map(|i| &self.outputs[*i]).collect();
+        let at = rects.iter().position(|r| std::ptr::eq(*r, &self.outputs[output]))?;
+
+        let neighbour = alignment::next_in_direction(&rects, rects[at], &properties);
+        let target = match (neighbour, wrap) {
+            (Some(neighbour), _) => occupied[neighbour],
+            (None, Some(Wrap::Output)) => return local.first(direction).and_then(|w| self.find(w.id)),
+            (None, Some(Wrap::Global)) => occupied[alignment::first_of_direction(&rects, rects[at], &properties)?],
+            (None, None) => return None,
+        };
+        self.enter(target, direction, &current.rect)
+    }
+
+    /// Returns the index of the output showing the middle of the rectangle.
+    fn output_of(&self, rect: &Rect) -> Option<usize> {
+        let (x, y) = (rect.horizontal_middle(), rect.vertical_middle());
+        self.outputs.iter().position(|o| o.left() <= x && x < o.right() && o.top() <= y && y < o.bottom())
+    }
+
+    /// Returns the indices of the outputs that show any window.
+    fn occupied_outputs(&self) -> Vec<usize> {
+        (0..self.outputs.len())
+            .filter(|i| self.windows.iter().any(|w| self.output_of(&w.rect) == Some(*i)))
+            .collect()
+    }
+
+    /// Returns the arrangement of windows on the output, positioned at the current window if it
+    /// is there.
+    fn arrangement_of(&self, output: usize) -> Arrangement {
+        let windows: Vec<Window> = self.windows.iter()
+            .filter(|w| self.output_of(&w.rect) == Some(output))
+            .cloned()
+            .collect();
+        let current = windows.iter().position(|w| w.id == self.windows[self.current].id);
+        Arrangement::new(windows, current, Some(self.relation))
+    }
+
+    /// Returns the first window on the output when coming from the rectangle in the direction.
+    fn enter(&self, output: usize, direction: Direction, from: &Rect) -> Option<&Window> {
+        let windows: Vec<&Window> = self.windows.iter()
+            .filter(|w| self.output_of(&w.rect) == Some(output))
+            .collect();
+        let rects: Vec<&Rect> = windows.iter().map(|w| &w.rect).collect();
+        let properties = alignment::get_properties(self.relation, direction);
+        let first = alignment::first_of_direction(&rects, from, &properties)?;
+        Some(windows[first])
+    }
+
+    /// Navigates all windows as one plane, wrapping around its edges with any `wrap`.
+    fn next_in_plane(&self, direction: Direction, wrap: Option<Wrap>) -> Option<&Window> {
+        let mut plane = Arrangement::new(self.windows.clone(), Some(self.current), Some(self.relation));
+        let window = match plane.next(direction) {
+            Some(window) => Some(window.id),
+            None if wrap.is_some() => plane.first(direction).map(|w| w.id),
+            None => None,
+        };
+        self.find(window?)
+    }
+
+    fn find(&self, window_id: u64) -> Option<&Window> {
+        self.windows.iter().find(|w| w.id == window_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u64, x: i32, y: i32, w: i32, h: i32) -> Window {
+        Window { id, rect: Rect { x, y, w, h }, ..Default::default() }
+    }
+
+    // The layout of the outputs and windows is as follows, the right output is taller and placed
+    // lower, with a gap between the outputs:
+    //
+    //   ┌────────┬────────┐
+    //   │        │        │
+    //   │   1    │   2    │      ┌─────────────────┐
+    //   │        │        │      │        4        │
+    //   ├────────┴────────┤      ├─────────────────┤
+    //   │        3        │      │        5        │
+    //   └─────────────────┘      │                 │
+    //                            └─────────────────┘
+    fn layout(current: usize) -> Layout {
+        let outputs = vec![
+            Rect { x: 0, y: 0, w: 200, h: 200 },
+            Rect { x: 300, y: 50, w: 200, h: 250 },
+        ];
+        let windows = vec![
+            window(1, 0, 0, 100, 100),
+            window(2, 100, 0, 100, 100),
+            window(3, 0, 100, 200, 100),
+            window(4, 300, 50, 200, 100),
+            window(5, 300, 150, 200, 150),
+        ];
+        Layout::new(outputs, windows, Some(current), Some(Relation::Border))
+    }
+
+    fn next(layout: &Layout, direction: Direction, wrap: Option<Wrap>) -> Option<u64> {
+        layout.next(direction, wrap).map(|w| w.id)
+    }
+
+    // In this test we expect the current output to be searched first and the neighbouring one
+    // to be entered from the side facing the current window.
+    #[test]
+    fn test_next_across_outputs() {
+        assert_eq!(next(&layout(0), Direction::Right, None), Some(2));
+        assert_eq!(next(&layout(1), Direction::Right, None), Some(4));
+        assert_eq!(next(&layout(2), Direction::Right, None), Some(4));
+        assert_eq!(next(&layout(3), Direction::Left, None), Some(2));
+        assert_eq!(next(&layout(4), Direction::Left, None), Some(3));
+        assert_eq!(next(&layout(3), Direction::Down, None), Some(5));
+        assert_eq!(next(&layout(4), Direction::Down, None), None);
+        assert_eq!(next(&layout(0), Direction::Left, None), None);
+    }
+
+    // In this test we expect output wrapping to stay on the current output and global wrapping
+    // to continue on the output at the other end.
+    #[test]
+    fn test_next_wrap() {
+        assert_eq!(next(&layout(3), Direction::Right, Some(Wrap::Output)), Some(4));
+        assert_eq!(next(&layout(4), Direction::Right, Some(Wrap::Global)), Some(3));
+        assert_eq!(next(&layout(3), Direction::Right, Some(Wrap::Global)), Some(1));
+        assert_eq!(next(&layout(0), Direction::Left, Some(Wrap::Output)), Some(2));
+        assert_eq!(next(&layout(0), Direction::Left, Some(Wrap::Global)), Some(4));
+        assert_eq!(next(&layout(4), Direction::Down, Some(Wrap::Output)), Some(4));
+    }
+
+    // In this test we expect outputs without windows to be skipped, and windows outside of the
+    // known outputs to be navigated as one plane.
+    #[test]
+    fn test_next_without_outputs() {
+        let mut layout = layout(1);
+        layout.outputs.insert(1, Rect { x: 200, y: 0, w: 100, h: 100 });
+        assert_eq!(next(&layout, Direction::Right, None), Some(4));
+
+        layout.outputs.clear();
+        assert_eq!(next(&layout, Direction::Right, None), Some(4));
+        assert_eq!(next(&layout, Direction::Left, Some(Wrap::Output)), Some(1));
+    }
+}
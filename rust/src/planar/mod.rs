@@ -1,6 +1,9 @@
 pub mod alignment;
 pub mod arrangement;
+pub mod layout;
 
 pub use alignment::Direction;
 pub use alignment::Relation;
 pub use arrangement::Arrangement;
+pub use layout::Layout;
+pub use layout::Wrap;
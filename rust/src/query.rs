@@ -0,0 +1,377 @@
+//! Query mode prints what i3switch sees and where a command would lead, without focusing
+//! anything, to debug bindings and to feed scripts. Dry runs of commands print just the target
+//! and the reason for it.
+//!
+//! Reports are written by hand rather than serialized, JSON support comes with the i3 backend
+//! only and the other backends have to print it as well.
+
+use crate::backend::traits::{GetAll, GetContainers, GetGroups, GetOutputs, GetScratchpad, GetTabs, GetVisible, GetWorkspaces};
+use crate::cli::Cli;
+use crate::error::{self, Result};
+use crate::linear;
+use crate::navigation;
+use crate::planar;
+use crate::types::{TabLevel, Window, Windows};
+
+use std::path::Path;
+
+/// What the query found out. Everything besides the windows is known only for targets.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub windows: Windows,
+    pub scope: Option<String>,
+    pub command: Option<String>,
+    pub relation: Option<planar::Relation>,
+    pub sequence: Option<Vec<u64>>,
+    pub target: Option<u64>,
+}
+
+/// Prints the report asked for by the command and returns the process exit code. Targets are
+/// found with the history and the order of urgent windows kept in the runtime directory.
+pub fn execute<B>(cli: &Cli, backend: &B, runtime: Option<&Path>) -> Result<i32>
+where
+    B: GetVisible + GetTabs + GetGroups + GetOutputs + GetAll + GetWorkspaces + GetScratchpad
+        + GetContainers,
+{
+    let report = match (cli.report.as_deref(), &cli.target) {
+        (Some("windows"), _) => Report::of_windows(backend.get_visible()?),
+        (Some("tabs"), _) => Report::of_windows(backend.get_tabs()?),
+        (_, Some(target)) => report_target(target, backend, runtime)?,
+        _ => unreachable!("No valid query provided. This should not happen."),
+    };
+    let output = if cli.format == "json" { report.to_json() } else { report.to_table() };
+    println!("{}", output);
+    Ok(error::EXIT_SUCCESS)
+}
+
+/// Prints the window or workspace the command leads to, or `none`, with the reason, and returns
+/// the process exit code the command would return.
+pub fn print_dry_run(cli: &Cli, target: Option<u64>) -> i32 {
+    let reason = reason(cli, target.is_some());
+    if cli.format == "json" {
+        println!("{{\"target\":{},\"reason\":{}}}",
+            target.map_or("null".to_string(), |t| t.to_string()), json_string(&reason));
+    } else {
+        println!("{}\t{}", target.map_or("none".to_string(), |t| t.to_string()), reason);
+    }
+    if target.is_some() { error::EXIT_SUCCESS } else { error::EXIT_NOTHING }
+}
+
+/// Explains what the command looks for, and what would be done with it.
+fn reason(cli: &Cli, found: bool) -> String {
+    let workspace = cli.scope == "workspace";
+    let sequential = match cli.scope.as_str() {
+        "workspace" => "workspace",
+        "scratchpad" => "scratchpad window",
+        _ => "tab or floating window",
+    };
+    let mut wanted = if let Some(direction) = cli.linear_direction() {
+        format!("{} {}", linear_name(direction), sequential)
+    } else if let Some(direction) = cli.planar_direction() {
+        format!("{} {}", if workspace { "workspace" } else { "window" }, planar_name(direction))
+    } else if let Some(direction) = cli.history_direction() {
+        format!("{} window in focus history", linear_name(direction))
+    } else if cli.command == "back" {
+        "previously focused window".to_string()
+    } else if cli.command == "parent" {
+        "enclosing container".to_string()
+    } else if cli.command == "child" {
+        "remembered child container".to_string()
+    } else if let Some(query) = &cli.query {
+        format!("window best matching '{}'", query)
+    } else if cli.command == "raise-or-run" {
+        format!("window meeting {}", cli.criteria)
+    } else if cli.command == "urgent" {
+        "window urgent for the longest time".to_string()
+    } else if let Some(number) = cli.number {
+        format!("{} number {}", sequential, number)
+    } else {
+        cli.command.clone()
+    };
+    if cli.group {
+        wanted.push_str(", grouping tabbed and stacked containers");
+    }
+    match cli.navigation.tabs {
+        TabLevel::Inner(0) => {}
+        TabLevel::Inner(level) => wanted.push_str(&format!(", in tabs {} levels above the innermost", level)),
+        TabLevel::Outer => wanted.push_str(", in the outermost tabs"),
+    }
+    match cli.wrap {
+        Some(planar::Wrap::Output) if cli.planar_direction().is_some() => wanted.push_str(", wrapping around the output"),
+        Some(_) => wanted.push_str(", wrapping around"),
+        None => {}
+    }
+
+    if !found {
+        if cli.command == "raise-or-run" {
+            return format!("no {}, would run {:?}", wanted, cli.run);
+        }
+        return format!("no {}", wanted);
+    }
+    match cli.action.as_str() {
+        "move" => format!("would move focused window to the {}", wanted),
+        "swap" => format!("would swap focused window with the {}", wanted),
+        _ if cli.scope == "scratchpad" => format!("would show the {}", wanted),
+        _ => format!("would focus the {}", wanted),
+    }
+}
+
+fn linear_name(direction: linear::Direction) -> &'static str {
+    match direction {
+        linear::Direction::Next => "next",
+        linear::Direction::Prev => "previous",
+    }
+}
+
+fn planar_name(direction: planar::Direction) -> &'static str {
+    match direction {
+        planar::Direction::Left => "to the left",
+        planar::Direction::Right => "to the right",
+        planar::Direction::Up => "above",
+        planar::Direction::Down => "below",
+    }
+}
+
+/// Navigates the way the command would, reporting what it navigated through.
+/// The focus history is caught up with the focus, but not saved.
+fn report_target<B>(cli: &Cli, backend: &B, runtime: Option<&Path>) -> Result<Report>
+where
+    B: GetVisible + GetTabs + GetGroups + GetOutputs + GetAll + GetWorkspaces + GetScratchpad
+        + GetContainers,
+{
+    let mut report = Report {
+        scope: Some(cli.scope.clone()),
+        command: Some(cli.command.clone()),
+        ..Default::default()
+    };
+
+    if cli.scope == "workspace" {
+        report.sequence = Some(navigation::get_workspace_sequence(backend)?.items().to_vec());
+        report.relation = cli.planar_direction().map(|_| planar::Relation::Border);
+        report.target = crate::app::find_workspace(cli, backend)?;
+        return Ok(report);
+    }
+
+    if cli.scope == "scratchpad" {
+        report.windows = navigation::get_scratchpad_windows(backend, &cli.navigation)?;
+        report.sequence = Some(report.windows.iter().map(|w| w.id).collect());
+        report.target = crate::app::find_scratchpad_window(cli, backend)?;
+        return Ok(report);
+    }
+
+    let history_command = cli.history_direction().is_some() || cli.command == "back";
+    if cli.linear_direction().is_some() || cli.number.is_some() {
+        report.windows = navigation::get_linear_windows(backend, &cli.navigation)?;
+        report.sequence = Some(report.windows.iter().map(|w| w.id).collect());
+    } else if cli.planar_direction().is_some() {
+        let layout = navigation::get_planar_layout(backend, &cli.navigation, cli.group)?;
+        report.relation = Some(layout.relation);
+        report.windows = layout.windows;
+    } else if !history_command {
+        report.windows = backend.get_all()?;
+    }
+
+    let (mut history, _) = crate::app::load_history(cli, backend, runtime)?;
+    let mut urgency = runtime.map(crate::urgency::Urgency::load).unwrap_or_default();
+    report.target = crate::app::find_window(cli, backend, &mut history, &mut urgency)?;
+    if history_command {
+        report.sequence = Some(history.sequence().items().to_vec());
+    }
+    Ok(report)
+}
+
+impl Report {
+    fn of_windows(windows: Windows) -> Self {
+        Report { windows, ..Default::default() }
+    }
+
+    /// Formats the report as a JSON object, with the target fields only for targets.
+    pub fn to_json(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(command) = &self.command {
+            fields.push(format!("\"scope\":{}", json_optional(self.scope.as_deref())));
+            fields.push(format!("\"command\":{}", json_string(command)));
+            fields.push(format!("\"relation\":{}", json_optional(self.relation.map(relation_name).as_deref())));
+            fields.push(format!("\"sequence\":{}", match &self.sequence {
+                Some(sequence) => format!("[{}]", join(sequence, ",")),
+                None => "null".to_string(),
+            }));
+            fields.push(format!("\"target\":{}", self.target.map_or("null".to_string(), |t| t.to_string())));
+        }
+        let windows: Vec<String> = self.windows.iter().map(window_json).collect();
+        fields.push(format!("\"windows\":[{}]", windows.join(",")));
+        format!("{{{}}}", fields.join(","))
+    }
+
+    /// Formats the report as a plain table of windows, preceded by the target fields for targets.
+    pub fn to_table(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(command) = &self.command {
+            lines.push(format!("scope:    {}", self.scope.as_deref().unwrap_or("-")));
+            lines.push(format!("command:  {}", command));
+            lines.push(format!("relation: {}", self.relation.map(relation_name).as_deref().unwrap_or("-")));
+            lines.push(format!("sequence: {}", self.sequence.as_ref().map_or("-".to_string(), |s| join(s, " "))));
+            lines.push(format!("target:   {}", self.target.map_or("none".to_string(), |t| t.to_string())));
+            if self.windows.is_empty() {
+                return lines.join("\n");
+            }
+            lines.push(String::new());
+        }
+        lines.push(format!("{:<12} {:>6} {:>6} {:>6} {:>6}  {:<23} {:<20} {}",
+            "ID", "X", "Y", "WIDTH", "HEIGHT", "STATE", "CLASS", "TITLE"));
+        for window in &self.windows {
+            let properties = &window.properties;
+            let class = properties.class.as_deref().or(properties.app_id.as_deref()).unwrap_or("-");
+            lines.push(format!("{:<12} {:>6} {:>6} {:>6} {:>6}  {:<23} {:<20} {}",
+                window.id, window.rect.x, window.rect.y, window.rect.w, window.rect.h,
+                state(window), class, properties.title.as_deref().unwrap_or("-")));
+        }
+        lines.join("\n")
+    }
+}
+
+fn window_json(window: &Window) -> String {
+    let properties = &window.properties;
+    let marks: Vec<String> = properties.marks.iter().map(|m| json_string(m)).collect();
+    format!(concat!("{{\"id\":{},\"rect\":{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}},",
+            "\"focused\":{},\"floating\":{},\"urgent\":{},\"fullscreen\":{},",
+            "\"class\":{},\"instance\":{},\"title\":{},\"app_id\":{},\"marks\":[{}],",
+            "\"type\":{},\"shell\":{},\"pid\":{}}}"),
+        window.id, window.rect.x, window.rect.y, window.rect.w, window.rect.h,
+        window.focused, window.floating, window.urgent, window.fullscreen,
+        json_optional(properties.class.as_deref()),
+        json_optional(properties.instance.as_deref()),
+        json_optional(properties.title.as_deref()),
+        json_optional(properties.app_id.as_deref()),
+        marks.join(","),
+        json_optional(properties.window_type.as_deref()),
+        json_optional(properties.shell.as_deref()),
+        properties.pid.map_or("null".to_string(), |pid| pid.to_string()))
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_optional(text: Option<&str>) -> String {
+    text.map_or("null".to_string(), json_string)
+}
+
+fn relation_name(relation: planar::Relation) -> String {
+    format!("{:?}", relation).to_lowercase()
+}
+
+fn state(window: &Window) -> String {
+    let flags = [(window.focused, "focused"), (window.floating, "floating"), (window.urgent, "urgent"),
+        (window.fullscreen, "fullscreen")];
+    let state: Vec<&str> = flags.iter().filter(|(set, _)| *set).map(|(_, name)| *name).collect();
+    if state.is_empty() { "-".to_string() } else { state.join(",") }
+}
+
+fn join(ids: &[u64], separator: &str) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Properties, Rect};
+    use crate::config::Config;
+
+    fn report() -> Report {
+        let windows = vec![
+            Window {
+                id: 11, rect: Rect { x: 0, y: 0, w: 960, h: 1080 }, focused: true,
+                properties: Properties {
+                    class: Some("Alacritty".to_string()),
+                    title: Some("vim \"notes\"".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Window {
+                id: 12, rect: Rect { x: 960, y: 0, w: 960, h: 1080 }, urgent: true,
+                properties: Properties {
+                    app_id: Some("firefox".to_string()),
+                    marks: vec!["web".to_string()],
+                    shell: Some("xdg_shell".to_string()),
+                    pid: Some(4242),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ];
+        Report {
+            windows,
+            scope: Some("window".to_string()),
+            command: Some("right".to_string()),
+            relation: Some(planar::Relation::Border),
+            sequence: None,
+            target: Some(12),
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(report().to_json(), concat!(
+            "{\"scope\":\"window\",\"command\":\"right\",\"relation\":\"border\",\"sequence\":null,\"target\":12,",
+            "\"windows\":[",
+            "{\"id\":11,\"rect\":{\"x\":0,\"y\":0,\"width\":960,\"height\":1080},",
+            "\"focused\":true,\"floating\":false,\"urgent\":false,\"fullscreen\":false,",
+            "\"class\":\"Alacritty\",\"instance\":null,\"title\":\"vim \\\"notes\\\"\",\"app_id\":null,\"marks\":[],",
+            "\"type\":null,\"shell\":null,\"pid\":null},",
+            "{\"id\":12,\"rect\":{\"x\":960,\"y\":0,\"width\":960,\"height\":1080},",
+            "\"focused\":false,\"floating\":false,\"urgent\":true,\"fullscreen\":false,",
+            "\"class\":null,\"instance\":null,\"title\":null,\"app_id\":\"firefox\",\"marks\":[\"web\"],",
+            "\"type\":null,\"shell\":\"xdg_shell\",\"pid\":4242}",
+            "]}"));
+
+        let windows = Report::of_windows(Vec::new());
+        assert_eq!(windows.to_json(), "{\"windows\":[]}");
+        assert_eq!(json_string("tab\there\u{1}"), "\"tab\\there\\u0001\"");
+    }
+
+    #[test]
+    fn test_reason() {
+        let parse = |line: &str| Cli::parse(line.split_whitespace().map(String::from).collect(), &Config::default());
+        assert_eq!(reason(&parse("i3switch right group wrap=output"), true),
+            "would focus the window to the right, grouping tabbed and stacked containers, wrapping around the output");
+        assert_eq!(reason(&parse("i3switch swap up"), true), "would swap focused window with the window above");
+        assert_eq!(reason(&parse("i3switch workspace next wrap"), false), "no next workspace, wrapping around");
+        assert_eq!(reason(&parse("i3switch number 2"), true), "would focus the tab or floating window number 2");
+        assert_eq!(reason(&parse("i3switch scratchpad next wrap"), true), "would show the next scratchpad window, wrapping around");
+        assert_eq!(reason(&parse("i3switch mru-prev"), false), "no previous window in focus history");
+        assert_eq!(reason(&parse("i3switch raise-or-run --mark term -- alacritty"), false),
+            "no window meeting --mark \"term\", would run [\"alacritty\"]");
+    }
+
+    #[test]
+    fn test_to_table() {
+        let table = report().to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "scope:    window");
+        assert_eq!(lines[2], "relation: border");
+        assert_eq!(lines[3], "sequence: -");
+        assert_eq!(lines[4], "target:   12");
+        assert!(lines[6].starts_with("ID"));
+        assert!(lines[7].starts_with("11") && lines[7].contains("focused") && lines[7].ends_with("vim \"notes\""));
+        assert!(lines[8].contains("urgent") && lines[8].contains("firefox"));
+
+        let windows = Report::of_windows(Vec::new());
+        assert!(windows.to_table().starts_with("ID"));
+    }
+}
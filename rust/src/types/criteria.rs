@@ -0,0 +1,153 @@
+use crate::types::Window;
+
+use std::fmt;
+
+/// Criteria selecting windows by their properties, a window has to meet all of the given ones.
+/// Names are compared ignoring case, the title only has to contain the given text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Criteria {
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub title: Option<String>,
+    pub app_id: Option<String>,
+    pub mark: Option<String>,
+    /// Window type, like `normal`, `dialog` or `notification`, ignoring underscores.
+    pub window_type: Option<String>,
+    pub floating: Option<bool>,
+}
+
+impl Criteria {
+    /// Returns whether no criterion is given, which would match every window.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parses criteria written as `key=value` pairs separated by commas, like
+    /// `class=mpv,floating=true`. Keys are `class`, `instance`, `title`, `app-id`, `mark`, `type`
+    /// and `floating`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut criteria = Criteria::default();
+        for pair in text.split(',') {
+            let (key, value) = pair.split_once('=')
+                .ok_or(format!("Expected 'key=value' in '{}'", pair))?;
+            let field = match key.trim() {
+                "class" => &mut criteria.class,
+                "instance" => &mut criteria.instance,
+                "title" => &mut criteria.title,
+                "app-id" => &mut criteria.app_id,
+                "mark" => &mut criteria.mark,
+                "type" => &mut criteria.window_type,
+                "floating" => {
+                    criteria.floating = Some(value.trim().parse::<bool>()
+                        .map_err(|_| format!("Expected 'true' or 'false' for 'floating', not '{}'", value))?);
+                    continue;
+                }
+                key => return Err(format!("Unknown criterion '{}'", key)),
+            };
+            *field = Some(value.trim().to_string());
+        }
+        Ok(criteria)
+    }
+
+    pub fn matches(&self, window: &Window) -> bool {
+        let properties = &window.properties;
+        let equals = |wanted: &Option<String>, actual: &Option<String>| match (wanted, actual) {
+            (None, _) => true,
+            (Some(wanted), Some(actual)) => wanted.eq_ignore_ascii_case(actual),
+            (Some(_), None) => false,
+        };
+        let title = match (&self.title, &properties.title) {
+            (None, _) => true,
+            (Some(wanted), Some(actual)) => actual.to_lowercase().contains(&wanted.to_lowercase()),
+            (Some(_), None) => false,
+        };
+        let mark = match &self.mark {
+            None => true,
+            Some(wanted) => properties.marks.contains(wanted),
+        };
+        // Backends spell types differently, dropdown_menu is DropDownMenu elsewhere
+        let window_type = |window_type: &Option<String>| window_type.as_ref().map(|t| t.replace('_', ""));
+        equals(&self.class, &properties.class)
+            && equals(&self.instance, &properties.instance)
+            && equals(&self.app_id, &properties.app_id)
+            && equals(&window_type(&self.window_type), &window_type(&properties.window_type))
+            && self.floating.is_none_or(|floating| floating == window.floating)
+            && title
+            && mark
+    }
+}
+
+/// Formats the given criteria the way they are given on the command line.
+impl fmt::Display for Criteria {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let criteria = [
+            ("class", &self.class),
+            ("instance", &self.instance),
+            ("title", &self.title),
+            ("app-id", &self.app_id),
+            ("mark", &self.mark),
+            ("type", &self.window_type),
+        ];
+        let mut given: Vec<String> = criteria.iter()
+            .filter_map(|(name, value)| Some(format!("--{} {:?}", name, value.as_ref()?)))
+            .collect();
+        if let Some(floating) = self.floating {
+            given.push(format!("--floating {}", floating));
+        }
+        write!(f, "{}", given.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::types::{Properties, Rect};
+
+    #[test]
+    fn test_matches() {
+        let properties = Window {
+            id: 1, rect: Rect { x: 0, y: 0, w: 100, h: 100 }, floating: true,
+            properties: Properties {
+                class: Some("Firefox".to_string()),
+                instance: Some("Navigator".to_string()),
+                title: Some("Docs - Mozilla Firefox".to_string()),
+                marks: vec!["web".to_string()],
+                window_type: Some("dropdown_menu".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let criteria = |class: Option<&str>, title: Option<&str>, mark: Option<&str>| Criteria {
+            class: class.map(String::from),
+            title: title.map(String::from),
+            mark: mark.map(String::from),
+            ..Default::default()
+        };
+        assert!(criteria(Some("firefox"), None, None).matches(&properties));
+        assert!(criteria(Some("firefox"), Some("docs"), Some("web")).matches(&properties));
+        assert!(!criteria(Some("fire"), None, None).matches(&properties));
+        assert!(!criteria(Some("firefox"), Some("mail"), None).matches(&properties));
+        assert!(!criteria(None, None, Some("Web")).matches(&properties));
+        assert!(!Criteria { app_id: Some("firefox".to_string()), ..Default::default() }.matches(&properties));
+        assert!(Criteria::default().is_empty());
+        assert!(!criteria(Some("firefox"), None, None).is_empty());
+        assert!(Criteria { floating: Some(true), ..Default::default() }.matches(&properties));
+        assert!(!Criteria { floating: Some(false), ..Default::default() }.matches(&properties));
+        assert!(Criteria { window_type: Some("DropDownMenu".to_string()), ..Default::default() }.matches(&properties));
+        assert!(!Criteria { window_type: Some("menu".to_string()), ..Default::default() }.matches(&properties));
+    }
+
+    #[test]
+    fn test_parse() {
+        let criteria = Criteria::parse("class=mpv, floating=true,type=utility").unwrap();
+        assert_eq!(criteria.class.as_deref(), Some("mpv"));
+        assert_eq!(criteria.floating, Some(true));
+        assert_eq!(criteria.window_type.as_deref(), Some("utility"));
+        assert_eq!(criteria.to_string(), "--class \"mpv\" --type \"utility\" --floating true");
+        assert!(Criteria::parse("title=Picture-in-Picture").is_ok());
+        assert!(Criteria::parse("class").is_err());
+        assert!(Criteria::parse("floating=yes").is_err());
+        assert!(Criteria::parse("name=conky").is_err());
+    }
+}
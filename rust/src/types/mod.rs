@@ -1,6 +1,17 @@
 pub mod window;
+pub mod criteria;
 pub mod rect;
+pub mod output;
+pub mod workspace;
+pub mod tab_level;
 
 pub use window::Window;
 pub use window::Windows;
+pub use window::Properties;
+pub use criteria::Criteria;
 pub use rect::Rect;
+pub use output::Output;
+pub use output::Outputs;
+pub use workspace::Workspace;
+pub use workspace::Workspaces;
+pub use tab_level::TabLevel;
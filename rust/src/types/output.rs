@@ -0,0 +1,11 @@
+use crate::types::Rect;
+
+/// A collection of outputs, represented as a vector of `Output` structs.
+pub type Outputs = Vec<Output>;
+
+/// This struct represents an active output, a monitor showing a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub name: String,
+    pub rect: Rect,
+}
@@ -1,22 +1,20 @@
-/// A simple rectangle structure with methods to calculate its extents and middle points in a 2D
-/// space.
+//! A simple rectangle structure with methods to calculate its extents and middle points in a 2D
+//! space.
 
 #[cfg(feature = "i3")]
 use serde::Deserialize;
+use std::fmt;
 
 /// This structure is used to represent a rectangle defined by its top-left corner (x, y) and its
 /// width (w) and height (h).
-#[cfg(feature = "i3")]
-#[derive(Deserialize)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "i3", derive(Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
-    #[cfg(feature = "i3")]
-    #[serde(rename = "width")]
+    #[cfg_attr(feature = "i3", serde(rename = "width"))]
     pub w: i32,
-    #[cfg(feature = "i3")]
-    #[serde(rename = "height")]
+    #[cfg_attr(feature = "i3", serde(rename = "height"))]
     pub h: i32,
 }
 
@@ -47,8 +45,8 @@ impl Rect {
     }
 }
 
-impl ToString for Rect {
-    fn to_string(&self) -> String {
-        format!("Rect(x: {}, y: {}, w: {}, h: {})", self.x, self.y, self.w, self.h)
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rect(x: {}, y: {}, w: {}, h: {})", self.x, self.y, self.w, self.h)
     }
 }
@@ -0,0 +1,14 @@
+/// Which of the nested tabbed or stacked containers around the focused window is navigated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabLevel {
+    /// Counted outwards from the innermost container, which is level 0.
+    Inner(usize),
+    /// The outermost container.
+    Outer,
+}
+
+impl Default for TabLevel {
+    fn default() -> Self {
+        TabLevel::Inner(0)
+    }
+}
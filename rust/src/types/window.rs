@@ -4,12 +4,35 @@ use crate::types::Rect;
 pub type Windows = Vec<Window>;
 
 /// This enum represents a window in a window manager's tree structure.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Window {
     pub id: u64,
     pub rect: Rect,
     pub focused: bool,
     pub floating: bool,
+    /// The window demands attention, like a terminal that rang the bell.
+    pub urgent: bool,
+    /// The window covers its output, hiding the windows behind it.
+    pub fullscreen: bool,
+    pub properties: Properties,
+}
+
+/// What the window is, as told by its application and marked by the user.
+/// Backends fill in what they know, the rest is left empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Properties {
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub title: Option<String>,
+    /// Wayland application id, the equivalent of class for native sway windows.
+    pub app_id: Option<String>,
+    pub marks: Vec<String>,
+    /// EWMH window type without its prefix, like `normal`, `dialog` or `notification`.
+    pub window_type: Option<String>,
+    /// Wayland shell of the window in sway, `xdg_shell` or `xwayland`.
+    pub shell: Option<String>,
+    /// Process owning the window, when the window manager tells it.
+    pub pid: Option<u32>,
 }
 
 impl From<&Window> for Rect {
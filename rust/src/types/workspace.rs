@@ -0,0 +1,16 @@
+use crate::types::Rect;
+
+/// A collection of workspaces, represented as a vector of `Workspace` structs.
+pub type Workspaces = Vec<Workspace>;
+
+/// This struct represents a workspace, visible ones are shown on an output covered by the rect.
+/// The number is the one `workspace number` switches to, named workspaces may have none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub id: u64,
+    pub num: Option<usize>,
+    pub name: String,
+    pub rect: Rect,
+    pub focused: bool,
+    pub visible: bool,
+}
@@ -0,0 +1,89 @@
+//! Order in which windows became urgent, so that the one waiting the longest is attended first.
+//!
+//! Window managers only tell whether a window is urgent, not since when. The order is kept
+//! between invocations and caught up with the windows urgent at the time. The daemon catches up
+//! whenever i3 reports a window becoming urgent or attended, so with it running the order is
+//! exact.
+
+use crate::logging;
+
+use std::path::Path;
+
+/// Name of the file the order is saved in, in the runtime directory.
+const FILE: &str = "i3switch.urgent";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Urgency {
+    windows: Vec<u64>,
+}
+
+impl Urgency {
+    pub fn new(windows: Vec<u64>) -> Self {
+        Self { windows }
+    }
+
+    /// Loads the order saved in the runtime directory by previous invocations, or an empty one if
+    /// there is none.
+    pub fn load(dir: &Path) -> Self {
+        match std::fs::read_to_string(dir.join(FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Saves the order in the runtime directory for the next invocations.
+    pub fn save(&self, dir: &Path) {
+        if let Err(e) = std::fs::write(dir.join(FILE), self.serialize()) {
+            logging::warning!("Failed to save urgent windows: {}", e);
+        }
+    }
+
+    /// Catches up with the currently urgent windows. Windows no longer urgent are forgotten and
+    /// newly urgent ones go last, as the most recent. Returns whether anything changed.
+    pub fn update(&mut self, urgent: &[u64]) -> bool {
+        let before = self.windows.clone();
+        self.windows.retain(|id| urgent.contains(id));
+        let new: Vec<u64> = urgent.iter().filter(|id| !self.windows.contains(id)).copied().collect();
+        self.windows.extend(new);
+        self.windows != before
+    }
+
+    /// Returns the urgent windows, from the one urgent for the longest time.
+    pub fn windows(&self) -> &[u64] {
+        &self.windows
+    }
+
+    fn parse(content: &str) -> Self {
+        Self::new(content.lines().filter_map(|l| l.trim().parse::<u64>().ok()).collect())
+    }
+
+    fn serialize(&self) -> String {
+        self.windows.iter().map(|id| format!("{}\n", id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let mut urgency = Urgency::default();
+        assert!(urgency.update(&[3, 1]));
+        assert_eq!(urgency.windows(), &[3, 1]);
+
+        assert!(urgency.update(&[2, 1, 3]));
+        assert_eq!(urgency.windows(), &[3, 1, 2]);
+
+        assert!(!urgency.update(&[1, 2, 3]));
+        assert!(urgency.update(&[2]));
+        assert_eq!(urgency.windows(), &[2]);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let urgency = Urgency::new(vec![10, 20]);
+        assert_eq!(Urgency::parse(&urgency.serialize()), urgency);
+        assert_eq!(Urgency::parse(""), Urgency::default());
+    }
+}